@@ -18,6 +18,13 @@
 //!
 //! See the [`src/main.rs`](https://github.com/nabijaczleweli/cargo-update/blob/master/src/main.rs) file in the git repository.
 //!
+//! ## Logging
+//!
+//! With the `logging` feature, `cargo-update`'s messages go through the [`log`](https://docs.rs/log) crate:
+//! status messages at `info`, warnings at `warn`, errors at `error`, and registry update progress at `debug`,
+//! with `--quiet` raising the lowest level shown from `debug` to `warn`, then to `error`.
+//! [`ops::LogWriter`](ops/struct.LogWriter.html) logs the output of the functions here the same way.
+//!
 //! # Executable manpage
 //!
 //! ## SYNOPSIS
@@ -51,6 +58,7 @@
 //! -1 - cargo subprocess was terminated by a signal (Linux-only)
 //! 1  - option parsing error
 //! 2  - registry repository error
+//! 3  - another cargo-update is updating the same cargo directory, or a file couldn't be written
 //! X  - bubbled-up cargo install exit value
 //! ```
 //!
@@ -116,6 +124,26 @@
 //! ```text
 //! Don't print status messages to stdout
 //! and pass down --quiet to cargo subprocesses.
+//!
+//! Errors and the list of packages that failed to update are still printed.
+//! Specify twice to hide warnings and that list, too.
+//! ```
+//!
+//! --binstall <BINSTALL_EXECUTABLE>
+//!
+//! ```text
+//! cargo-binstall executable to try first for eligible packages,
+//! falling back to cargo install if it can't be run.
+//!
+//! --quiet, --locked, -j (as CARGO_BUILD_JOBS), and --target-dir are forwarded to it.
+//!
+//! Default: "$CARGO_UPDATE_BINSTALL", then "cargo-binstall"
+//! ```
+//!
+//! --no-binstall
+//!
+//! ```text
+//! Never use cargo-binstall, always install with cargo install.
 //! ```
 //!
 //! -s --filter <PACKAGE_FILTER>...
@@ -165,6 +193,10 @@
 //!
 //! Overrides the git executable in `net.git-fetch-with-cli=true` mode.
 //!
+//! ##### CARGO_UPDATE_BINSTALL
+//!
+//! Overrides the cargo-binstall executable, like `--binstall`.
+//!
 //! ## EXAMPLES
 //!
 //! `cargo install-update -a`
@@ -398,9 +430,11 @@ extern crate home;
 extern crate toml;
 extern crate hex;
 extern crate url;
+#[cfg(feature = "logging")]
+extern crate log;
 
 mod options;
 
 pub mod ops;
 
-pub use options::{ConfigOptions, Options};
+pub use options::{ConfigOptions, Options, read_package_list_in, read_package_list, package_parse_in, package_parse};
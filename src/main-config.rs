@@ -4,6 +4,8 @@ extern crate tabwriter;
 use std::io::{Write, stdout};
 use tabwriter::TabWriter;
 use std::process::exit;
+use std::borrow::Cow;
+use std::env;
 
 
 fn main() {
@@ -15,16 +17,12 @@ fn actual_main() -> Result<(), i32> {
     let opts = cargo_update::ConfigOptions::parse();
     let config_file = cargo_update::ops::crates_file_in(&opts.cargo_dir).with_file_name(".install_config.toml");
 
-    let mut configuration = cargo_update::ops::PackageConfig::read(&config_file, &config_file.with_file_name(".crates2.json")).map_err(|(e, r)| {
+    let mut configuration = cargo_update::ops::PackageConfig::read_unmerged(&config_file, &config_file.with_file_name(".crates2.json")).map_err(|(e, r)| {
             eprintln!("Reading config: {}", e);
             r
         })?;
     if !opts.ops.is_empty() {
-        if *configuration.entry(opts.package.clone())
-            .and_modify(|cfg| cfg.execute_operations(&opts.ops))
-            .or_insert_with(|| cargo_update::ops::PackageConfig::from(&opts.ops)) == Default::default() {
-            configuration.remove(&opts.package);
-        }
+        cargo_update::ops::PackageConfig::execute_operations_on(&mut configuration, opts.packages.iter().map(String::as_str), &opts.ops);
 
         cargo_update::ops::PackageConfig::write(&configuration, &config_file).map_err(|(e, r)| {
                 eprintln!("Writing config: {}", e);
@@ -32,7 +30,26 @@ fn actual_main() -> Result<(), i32> {
             })?;
     }
 
-    if let Some(cfg) = configuration.get(&opts.package) {
+    let defaults = configuration.get(cargo_update::ops::DEFAULT_CONFIG_SECTION);
+    for (i, package) in opts.packages.iter().enumerate() {
+        if opts.packages.len() > 1 {
+            if i != 0 {
+                println!();
+            }
+            println!("{}:", package);
+        }
+
+        let defaults = defaults.filter(|_| package != cargo_update::ops::DEFAULT_CONFIG_SECTION);
+        let cfg = match (configuration.get(package), defaults) {
+            (Some(cfg), Some(defaults)) => Cow::Owned(cfg.inherit(defaults)),
+            (Some(cfg), None) |
+            (None, Some(cfg)) => Cow::Borrowed(cfg),
+            (None, None) => {
+                println!("No configuration for package {}.", package);
+                continue;
+            }
+        };
+
         let mut out = TabWriter::new(stdout());
         if let Some(ref t) = cfg.toolchain {
             writeln!(out, "Toolchain\t{}", t).unwrap();
@@ -40,25 +57,49 @@ fn actual_main() -> Result<(), i32> {
         if let Some(p) = cfg.build_profile.as_deref().or_else(|| cfg.debug.and_then(|d| if d { Some("dev") } else { None })) {
             writeln!(out, "Build profile\t{}", p).unwrap();
         }
+        if let Some(ref t) = cfg.target {
+            writeln!(out, "Target\t{}", t).unwrap();
+        }
+        if let Some(ref gr) = cfg.git_ref {
+            writeln!(out, "Git ref\t{}", gr).unwrap();
+        }
         if let Some(ip) = cfg.install_prereleases {
             writeln!(out, "Install prereleases\t{}", ip).unwrap();
         }
         if let Some(el) = cfg.enforce_lock {
             writeln!(out, "Enforce lock\t{}", el).unwrap();
         }
+        if let Some(f) = cfg.frozen {
+            writeln!(out, "Frozen\t{}", f).unwrap();
+        }
+        if let Some(o) = cfg.offline {
+            writeln!(out, "Offline\t{}", o).unwrap();
+        }
         if let Some(rb) = cfg.respect_binaries {
             writeln!(out, "Respect binaries\t{}", rb).unwrap();
         }
         if let Some(ref tv) = cfg.target_version {
             writeln!(out, "Target version\t{}", tv).unwrap();
         }
+        if let Some(h) = cfg.hold {
+            writeln!(out, "Hold\t{}", h).unwrap();
+        }
         writeln!(out, "Default features\t{}", cfg.default_features).unwrap();
+        if cfg.all_features {
+            writeln!(out, "All features\t{}", cfg.all_features).unwrap();
+        }
         if !cfg.features.is_empty() {
             write!(out, "Features").unwrap();
             for f in &cfg.features {
                 writeln!(out, "\t{}", f).unwrap();
             }
         }
+        if !cfg.extra_args.is_empty() {
+            write!(out, "Install arguments").unwrap();
+            for a in &cfg.extra_args {
+                writeln!(out, "\t{}", a).unwrap();
+            }
+        }
         if let Some(env) = cfg.environment.as_ref() {
             if !env.is_empty() {
                 write!(out, "Environment variables").unwrap();
@@ -70,9 +111,19 @@ fn actual_main() -> Result<(), i32> {
                 }
             }
         }
+        if let Some(ref pi) = cfg.post_install {
+            writeln!(out, "Post-install command\t{}", pi).unwrap();
+        }
+        if let Some(j) = cfg.jobs {
+            writeln!(out, "Jobs\t{}", j).unwrap();
+        }
+        if let Some(rf) = cfg.rustflags_value(env::var("RUSTFLAGS").ok().as_deref()) {
+            writeln!(out, "RUSTFLAGS\t{}", rf).unwrap();
+        }
+        if let Some(ref ic) = cfg.install_cargo {
+            writeln!(out, "Install cargo\t{}", ic.display()).unwrap();
+        }
         out.flush().unwrap();
-    } else {
-        println!("No configuration for package {}.", opts.package);
     }
 
     Ok(())
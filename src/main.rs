@@ -1,27 +1,107 @@
 extern crate cargo_update;
 extern crate tabwriter;
 extern crate git2;
+extern crate fs2;
+#[cfg(feature = "logging")]
+extern crate log;
 
-use std::io::{ErrorKind as IoErrorKind, Write, stdout, sink};
+use std::io::{self, ErrorKind as IoErrorKind, IsTerminal, Write, stderr, stdout, stdin, sink};
 use std::fmt::{self, Formatter, Display};
-use std::process::{Command, exit};
-use std::collections::BTreeMap;
+use std::process::{ExitStatus, Command, exit};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter::FromIterator;
 use tabwriter::TabWriter;
 use std::ffi::OsStr;
-#[cfg(target_os="windows")]
-use std::fs::File;
+use std::path::Path;
+use std::fs::{OpenOptions, File};
+use fs2::FileExt;
 use std::env;
+use std::thread;
 use std::fs;
 
 
+// Messages go to stdout and stderr, or, with the logging feature, through the log crate (to StdLogger, by default)
+#[cfg(not(feature = "logging"))]
+macro_rules! info {
+    ($($arg:tt)*) => { println!($($arg)*) }
+}
+#[cfg(not(feature = "logging"))]
+macro_rules! warn {
+    ($($arg:tt)*) => { eprintln!($($arg)*) }
+}
+#[cfg(not(feature = "logging"))]
+macro_rules! error {
+    ($($arg:tt)*) => { eprintln!($($arg)*) }
+}
+#[cfg(feature = "logging")]
+macro_rules! info {
+    ($($arg:tt)*) => { ::log::info!($($arg)*) }
+}
+#[cfg(feature = "logging")]
+macro_rules! warn {
+    ($($arg:tt)*) => { ::log::warn!($($arg)*) }
+}
+#[cfg(feature = "logging")]
+macro_rules! error {
+    ($($arg:tt)*) => { ::log::error!($($arg)*) }
+}
+
+
 fn main() {
-    let result = actual_main().err().unwrap_or(0);
+    let opts = cargo_update::Options::parse();
+    #[cfg(feature = "logging")]
+    {
+        let _ = log::set_logger(&StdLogger);
+        log::set_max_level(match opts.quiet {
+            0 => log::LevelFilter::Debug,
+            1 => log::LevelFilter::Warn,
+            _ => log::LevelFilter::Error,
+        });
+    }
+    let mut updating = None;
+    let mut summary = cargo_update::ops::UpdateSummary::default();
+    let result = actual_main(&opts, &mut updating, &mut summary).err();
+    // Skipped registry errors may only be in the summary, and come before any other failure, so return the first one
+    let mut result = summary.exit_code().or(result).unwrap_or(0);
+
+    if let (true, Some(summary_json)) = (opts.update, opts.summary_json.as_ref()) {
+        if let Err(e) = summary.write(summary_json) {
+            error!("Summary: {}", e);
+            if result == 0 {
+                result = -1;
+            }
+        }
+    }
+
+    if let (Some(packages), Some(post_command)) = (updating, opts.post_command.as_ref()) {
+        match cargo_update::ops::shell_command(post_command, &opts.temp_dir)
+            .env("CARGO_UPDATE_PACKAGES", packages.to_string())
+            .env("CARGO_UPDATE_RESULT", result.to_string())
+            .status() {
+            Ok(status) if !status.success() => {
+                error!("Post-command failed.");
+                if result == 0 {
+                    result = status.code().unwrap_or(-1);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Running post-command: {}", e);
+                if result == 0 {
+                    result = -1;
+                }
+            }
+        }
+    }
+
     exit(result);
 }
 
-fn actual_main() -> Result<(), i32> {
-    let opts = cargo_update::Options::parse();
+fn actual_main(opts: &cargo_update::Options, updating: &mut Option<usize>, summary: &mut cargo_update::ops::UpdateSummary) -> Result<(), i32> {
 
     if cfg!(target_os = "windows") {
         for old_version in fs::read_dir(env::current_exe().unwrap().parent().unwrap().canonicalize().unwrap())
@@ -32,60 +112,265 @@ fn actual_main() -> Result<(), i32> {
         }
     }
 
+    if let Some(cargo_home) = cargo_update::ops::cargo_home_mismatch(&opts.cargo_dir.1,
+                                                                     env::var_os("CARGO_HOME").as_deref(),
+                                                                     env::var_os("CARGO_INSTALL_ROOT").as_deref()) {
+        if opts.quiet < 2 {
+            warn!("Warning: $CARGO_HOME ({}) differs from the cargo directory ({}); cargo will use the configuration and registries from the former.",
+                      cargo_home.display(),
+                      opts.cargo_dir.1.display());
+        }
+    }
+
     let crates_file = cargo_update::ops::crates_file_in(&opts.cargo_dir.1);
+    let _lock = if opts.update && !opts.dry_run && !opts.list_executables {
+        Some(lock_install_root(opts, &crates_file)?)
+    } else {
+        None
+    };
+    if opts.clean_index_cache {
+        cargo_update::ops::clean_sparse_cache(&opts.cargo_dir.1).map_err(|e| {
+            error!("Couldn't clean index cache: {}.", e);
+            2
+        })?;
+    }
+    if let Some(remove) = opts.gc {
+        gc_git_clones(opts, &crates_file, remove)?;
+    }
+    if opts.list_executables {
+        list_executables(opts, &crates_file);
+        return Ok(());
+    }
+    let nothing_to_update = opts.update && !opts.all && opts.to_update.is_empty() && opts.from_snapshot.is_empty();
+    if (opts.clean_index_cache || opts.gc.is_some()) && nothing_to_update {
+        return Ok(());
+    }
+
     let http_proxy = cargo_update::ops::find_proxy(&crates_file);
-    let configuration = cargo_update::ops::PackageConfig::read(&crates_file.with_file_name(".install_config.toml"),
-                                                               &crates_file.with_file_name(".crates2.json")).map_err(|(e, r)| {
-            eprintln!("Reading config: {}", e);
+    let mut configuration = cargo_update::ops::PackageConfig::read(&crates_file.with_file_name(".install_config.toml"),
+                                                                   &crates_file.with_file_name(".crates2.json")).map_err(|(e, r)| {
+            error!("Reading config: {}", e);
             r
         })?;
-    let cargo_config = cargo_update::ops::CargoConfig::load(&crates_file);
-    let mut packages = cargo_update::ops::installed_registry_packages(&crates_file);
+    if let Some(snapshot_file) = opts.snapshot.as_ref() {
+        let installed = cargo_update::ops::InstalledPackages::read(&crates_file);
+        let snapshot = cargo_update::ops::SnapshotPackage::record(&installed.registry, &installed.git, &configuration);
+        cargo_update::ops::SnapshotPackage::write(&snapshot, snapshot_file).map_err(|e| {
+                error!("Writing snapshot to {}: {}.", snapshot_file.display(), e);
+                3
+            })?;
+        if opts.quiet == 0 {
+            info!("Recorded {} package{} to {}.", snapshot.len(), if snapshot.len() == 1 { "" } else { "s" }, snapshot_file.display());
+            info!("");
+        }
+        if nothing_to_update {
+            return Ok(());
+        }
+    }
+    // Restoring a snapshot, these apply to this run only
+    for (name, package) in &opts.from_snapshot {
+        if package.features.is_empty() && package.toolchain.is_none() && package.rev.is_none() {
+            continue;
+        }
+        let cfg = configuration.entry(name.clone()).or_default();
+        cfg.features.extend(package.features.iter().cloned());
+        if package.toolchain.is_some() {
+            cfg.toolchain = package.toolchain.clone();
+        }
+        if package.rev.is_some() {
+            cfg.git_ref = package.rev.clone();
+        }
+    }
+    let mut cargo_config = cargo_update::ops::CargoConfig::load(&crates_file);
+    if !opts.netrc {
+        cargo_config.http.netrc = None;
+    }
+    if opts.network_timeout.is_some() {
+        cargo_config.http.timeout = opts.network_timeout;
+    }
+    if let Some(timeout) = cargo_config.http.timeout {
+        let timeout = cmp::min(timeout.saturating_mul(1000), i32::MAX as u64) as i32;
+        // No other threads exist yet
+        unsafe {
+            let _ = git2::opts::set_server_connect_timeout_in_milliseconds(timeout);
+            let _ = git2::opts::set_server_timeout_in_milliseconds(timeout);
+        }
+    }
+    let mut registry_sources = cargo_update::ops::RegistrySources::load(&crates_file);
+    if let Ok(ref mut rs) = registry_sources {
+        rs.crates_io_index = opts.crates_io_index.clone();
+    }
+    let cargo_update::ops::InstalledPackages { registry: mut packages, git: installed_git_packages, path: mut path_packages } =
+        cargo_update::ops::InstalledPackages::read(&crates_file);
     let installed_git_packages = if opts.update_git || (opts.update && opts.install) {
-        cargo_update::ops::installed_git_repo_packages(&crates_file)
+        installed_git_packages
     } else {
         vec![]
     };
+    {
+        let mut package_registries = BTreeMap::<_, Vec<_>>::new();
+        for package in &packages {
+            package_registries.entry(&package.name[..]).or_default().push(&package.registry[..]);
+        }
+        for (name, registries) in package_registries.into_iter().filter(|(_, registries)| registries.len() > 1 && opts.quiet < 2) {
+            warn!("Warning: {} is installed from multiple registries ({}); each is updated from its own.", name, registries.join(", "));
+        }
+    }
+
+    let installed_names: Vec<_> = packages.iter().map(|p| &p.name[..]).chain(installed_git_packages.iter().map(|p| &p.name[..])).collect();
+    let (to_update, unmatched_globs) = cargo_update::ops::expand_package_globs(&opts.to_update, &installed_names);
+    // Held packages are only updated if named exactly (or restored from a snapshot)
+    let held = |name: &str| {
+        configuration.get(name).and_then(|c| c.hold).unwrap_or(false) && !opts.to_update.iter().any(|u| u.0 == name) &&
+        !opts.from_snapshot.contains_key(name)
+    };
+    for glob in unmatched_globs {
+        if opts.quiet < 2 && !path_packages.iter().any(|p| cargo_update::ops::package_name_glob_matches(&p.name, &glob)) {
+            warn!("Warning: no installed packages match {}.", glob);
+        }
+    }
+
+    if let Some((ref name, ref version)) = opts.compare_only {
+        let cfg = configuration.get(name);
+        let would_update = match packages.iter().find(|p| p.name == *name) {
+            Some(package) => {
+                package.needs_update_to(version,
+                                        cfg.and_then(|c| c.target_version.as_ref()),
+                                        cfg.and_then(|c| c.install_prereleases),
+                                        opts.downdate)
+            }
+            None => opts.install,
+        };
+        if opts.quiet == 0 {
+            info!("v{} {} an update for {}.", version, if would_update { "is" } else { "is not" }, name);
+        }
+        return if would_update { Ok(()) } else { Err(1) };
+    }
 
+    if !opts.prefixes.is_empty() {
+        packages.retain(|p| cargo_update::ops::package_name_has_prefix(&p.name, &opts.prefixes));
+        if packages.is_empty() && !installed_git_packages.iter().any(|p| cargo_update::ops::package_name_has_prefix(&p.name, &opts.prefixes)) {
+            error!("No installed packages start with {}.",
+                   opts.prefixes.iter().map(|p| format!("\"{}\"", p)).collect::<Vec<_>>().join(", "));
+        }
+    }
+    let bin_dir = crates_file.with_file_name("bin");
+    let resolve_installed = opts.filter.iter().any(|f| matches!(f, cargo_update::ops::PackageFilterElement::InstalledBefore(_)));
+    let installed_time = |executables: &[String]| if resolve_installed {
+        cargo_update::ops::installed_time(&bin_dir, executables)
+    } else {
+        None
+    };
+    path_packages.retain(|p| {
+        (opts.prefixes.is_empty() || cargo_update::ops::package_name_has_prefix(&p.name, &opts.prefixes)) &&
+        opts.filter
+            .iter()
+            .all(|f| f.matches_source(cargo_update::ops::PackageSource::Path, configuration.get(&p.name), installed_time(&p.executables))) &&
+        (opts.all || opts.to_update.is_empty() || opts.to_update.iter().any(|u| cargo_update::ops::package_name_glob_matches(&p.name, &u.0)))
+    });
+
+    let polled_filter = |f: &cargo_update::ops::PackageFilterElement| matches!(f, cargo_update::ops::PackageFilterElement::NeedsUpdate(_));
     if !opts.filter.is_empty() {
-        packages.retain(|p| configuration.get(&p.name).map(|p_cfg| opts.filter.iter().all(|f| f.matches(p_cfg))).unwrap_or(false));
+        let resolve_registries = opts.filter.iter().any(|f| matches!(f, cargo_update::ops::PackageFilterElement::Registry(_)));
+        packages.retain(|p| {
+            let registry_name = if resolve_registries {
+                registry_sources.as_ref()
+                    .ok()
+                    .and_then(|rs| cargo_update::ops::get_index_url(rs, &p.registry, cargo_config.registries_crates_io_protocol_sparse).ok())
+                    .map(|(_, _, name)| name)
+            } else {
+                None
+            };
+            let installed = installed_time(&p.executables);
+            opts.filter
+                .iter()
+                .filter(|f| !polled_filter(f))
+                .all(|f| f.matches_package(p, configuration.get(&p.name), registry_name.as_deref(), installed))
+        });
     }
     match (opts.all, opts.to_update.is_empty()) {
         (true, true) => {}
         (true, false) => {
-            for pkg in cargo_update::ops::intersect_packages(&packages, &opts.to_update, opts.install, &installed_git_packages).into_iter() {
-                if packages.iter().find(|p| p.name == pkg.name).is_none() {
+            let names: BTreeSet<_> = packages.iter().map(|p| p.name.clone()).collect();
+            for pkg in cargo_update::ops::intersect_packages(&packages, &to_update, opts.install, &installed_git_packages).into_iter() {
+                if !names.contains(&pkg.name) {
                     packages.push(pkg);
                 }
             }
         }
         (false, true) => {
             if opts.update {
-                panic!("No packages to update and neither --list nor --all specified, this should've been caught by option parser \
-                        (please report to http://github.com/nabijaczleweli/cargo-update)")
+                // Only reachable when list sources like --stdin-packages, --packages-from, or --from-manifest listed no packages
+                packages.clear();
             }
         }
-        (false, false) => packages = cargo_update::ops::intersect_packages(&packages, &opts.to_update, opts.install, &installed_git_packages),
+        (false, false) => packages = cargo_update::ops::intersect_packages(&packages, &to_update, opts.install, &installed_git_packages),
     }
 
     // These are all in the same order and (item => [package names]) maps
-    let mut registry_urls = BTreeMap::<_, Vec<_>>::new();
+    let mut registry_urls = BTreeMap::<_, BTreeSet<_>>::new();
+    // (package name, recorded registry) => item, since a name can be installed from multiple registries
+    let mut package_registries = BTreeMap::new();
+    // (package name, recorded registry) of packages from --exclude-registry or not --only-registry
+    let mut skipped_registry = BTreeSet::new();
+    // (package name, recorded registry) of packages whose registry couldn't be found or updated, with --keep-going
+    let mut failed_packages = BTreeSet::new();
+    // Resolve each recorded registry once, rather than once per package
+    let mut recorded_registries = BTreeMap::<_, Vec<_>>::new();
     for package in &packages {
-        registry_urls.entry(cargo_update::ops::get_index_url(&crates_file, &package.registry, cargo_config.registries_crates_io_protocol_sparse).map_err(|e| {
-                    eprintln!("Couldn't get registry for {}: {}.", package.name, e);
-                    2
-                })?)
-            .or_default()
-            .push(package.name.clone());
+        recorded_registries.entry((&package.registry[..], opts.prefer_installed_registry && package.version.is_some())).or_default().push(package);
+    }
+    for ((recorded_registry, prefer_recorded), registry_packages) in recorded_registries {
+        let get_index_url = if prefer_recorded {
+            cargo_update::ops::get_recorded_index_url
+        } else {
+            cargo_update::ops::get_index_url
+        };
+        match registry_sources.as_ref()
+            .map_err(Clone::clone)
+            .and_then(|rs| get_index_url(rs, recorded_registry, cargo_config.registries_crates_io_protocol_sparse)) {
+            Ok(registry) => {
+                let from = |r: &String| {
+                    cargo_update::ops::registry_matches(r, &registry.0, Some(&registry.2)) ||
+                    cargo_update::ops::registry_matches(r, recorded_registry, None)
+                };
+                if opts.exclude_registries.iter().any(from) || (!opts.only_registries.is_empty() && !opts.only_registries.iter().any(from)) {
+                    skipped_registry.extend(registry_packages.iter().map(|p| (p.name.clone(), p.registry.clone())));
+                    continue;
+                }
+
+                let pkg_names = registry_urls.entry(registry.clone()).or_default();
+                for package in registry_packages {
+                    pkg_names.insert(package.name.clone());
+                    package_registries.insert((package.name.clone(), package.registry.clone()), registry.clone());
+                }
+            }
+            Err(e) => {
+                for package in registry_packages {
+                    error!("Couldn't get registry for {}: {}.", package.name, e);
+                    if !opts.keep_going {
+                        return Err(2);
+                    }
+                    summary.failed.push((package.name.clone(), 2));
+                    failed_packages.insert((package.name.clone(), package.registry.clone()));
+                }
+            }
+        }
     }
-    let registry_urls: Vec<_> = registry_urls.into_iter().collect();
+    if !skipped_registry.is_empty() {
+        packages.retain(|p| !skipped_registry.contains(&(p.name.clone(), p.registry.clone())));
+    }
+    let registry_urls: Vec<_> = registry_urls.into_iter().map(|(registry, pkg_names)| (registry, Vec::from_iter(pkg_names))).collect();
+    let package_registries: BTreeMap<_, _> = package_registries.into_iter()
+        .map(|(pkg, registry)| (pkg, registry_urls.binary_search_by(|(r, _)| r.cmp(&registry)).unwrap()))
+        .collect();
 
     let registries: Vec<_> = Result::from_iter(registry_urls.iter()
         .map(|((registry_url, sparse, _), pkg_names)| {
             cargo_update::ops::assert_index_path(&opts.cargo_dir.1, &registry_url[..], *sparse)
                 .map(|path| (path, *sparse, &pkg_names[..]))
                 .map_err(|e| {
-                    eprintln!("Couldn't get package repository: {}.", e);
+                    error!("Couldn't get package repository: {}.", e);
                     2
                 })
         }))?;
@@ -93,254 +378,497 @@ fn actual_main() -> Result<(), i32> {
         cargo_update::ops::open_index_repository(registry, *sparse).map_err(|(init, e)| {
             match init {
                 true => {
-                    eprintln!("Failed to initialise fresh registry repository at {}: {}.\nTry running 'cargo search cargo-update' to initialise the \
+                    error!("Failed to initialise fresh registry repository at {}: {}.\nTry running 'cargo search cargo-update' to initialise the \
                                repository.",
                               registry.display(),
                               e)
                 }
-                false => eprintln!("Failed to open registry repository at {}: {}.", registry.display(), e),
+                false => error!("Failed to open registry repository at {}: {}.", registry.display(), e),
             }
             2
         })
     }))?;
+    let mut failed_registries = BTreeSet::new();
+    let deadline = opts.deadline.map(|d| Instant::now() + Duration::from_secs(d));
     for (i, mut registry_repo) in registry_repos.iter_mut().enumerate() {
+        let ((ref registry_url, sparse, _), ref pkg_names) = registry_urls[i];
+        let mut pkg_names: Vec<_> = pkg_names.iter().collect();
+        if let (true, cargo_update::ops::Registry::Sparse(ref mut registry)) = (opts.offline || (!opts.update && !opts.fresh_index), &mut registry_repo) {
+            // Listing doesn't need to be up-to-the-second, so use what cargo has already fetched where possible
+            let index_dirs = cargo_update::ops::registry_index_dirs(&opts.cargo_dir.1, registry_url);
+            pkg_names.retain(|pkg| match index_dirs.iter().find_map(|d| cargo_update::ops::read_sparse_cache(&opts.cargo_dir.1, d, pkg)) {
+                Some(versions) => {
+                    registry.insert(pkg.to_string(), versions);
+                    false
+                }
+                None => true,
+            });
+            if pkg_names.is_empty() {
+                continue;
+            }
+        }
+        if opts.offline {
+            if let cargo_update::ops::Registry::Sparse(ref mut registry) = registry_repo {
+                let cache = cargo_update::ops::SparseIndexCache::read(&cargo_update::ops::SparseIndexCache::path(&opts.cargo_dir.1, registry_url),
+                                                                      registry_url);
+                for pkg in pkg_names {
+                    if let Some(cached) = cache.packages.get(&pkg[..]) {
+                        registry.insert(pkg.to_string(), cached.versions.clone());
+                    }
+                }
+            }
+            continue;
+        }
+
+        let cache_path = cargo_update::ops::SparseIndexCache::path(&opts.cargo_dir.1, registry_url);
+        let mut cache = if sparse {
+            let mut cache = cargo_update::ops::SparseIndexCache::read(&cache_path, registry_url);
+            if opts.fresh_index {
+                for pkg in &pkg_names {
+                    cache.packages.remove(&pkg[..]);
+                }
+            }
+            Some(cache)
+        } else {
+            None
+        };
+
         cargo_update::ops::update_index(&mut registry_repo,
-                                        &(registry_urls[i].0).0,
-                                        registry_urls[i].1.iter(),
+                                        registry_url,
+                                        pkg_names.into_iter(),
                                         http_proxy.as_ref().map(String::as_str),
                                         cargo_config.net_git_fetch_with_cli,
+                                        opts.git_cli_fallback,
                                         &cargo_config.http,
-                                        &mut if !opts.quiet {
-                                            Box::new(stdout()) as Box<dyn Write>
+                                        cache.as_mut(),
+                                        opts.retries.or(cargo_config.net_retry).unwrap_or(2),
+                                        deadline,
+                                        opts.quiet == 0 && !cfg!(feature = "logging") && stdout().is_terminal(),
+                                        &mut if opts.quiet == 0 {
+                                            progress_output()
                                         } else {
                                             Box::new(sink()) as Box<dyn Write>
-                                        }).map_err(|e| {
-                eprintln!("Failed to update index repository {}: {}.", registry_urls[i].0.2, e);
-                2
-            })?;
+                                        }).unwrap_or_else(|e| {
+                error!("Failed to update index repository {}: {}.", registry_urls[i].0.2, e);
+                skip_failed_registry(opts, summary, &mut failed_packages, &registry_urls[i].0.2, &package_registries, i);
+                failed_registries.insert(i);
+            });
+
+        if let Some(cache) = cache {
+            if let Err(e) = cache.write(&cache_path) {
+                error!("Couldn't save index cache for {}: {}.", registry_urls[i].0.2, e);
+            }
+        }
     }
 
-    let latest_registries: Vec<_> = Result::from_iter(registry_repos.iter().zip(registries.iter()).map(|(registry_repo, (registry, ..))| {
-        cargo_update::ops::parse_registry_head(registry_repo).map_err(|e| {
-            eprintln!("Failed to read remote HEAD of registry repository at {}: {}.", registry.display(), e);
-            2
+    let latest_registries: Vec<_> = registry_repos.iter()
+        .zip(registries.iter())
+        .enumerate()
+        .map(|(i, (registry_repo, (registry, ..)))| {
+            if failed_registries.contains(&i) {
+                return None;
+            }
+            match cargo_update::ops::parse_registry_head(registry_repo) {
+                Ok(head) => Some(head),
+                Err(_) if opts.offline => None,
+                Err(e) => {
+                    error!("Failed to read remote HEAD of registry repository at {}: {}.", registry.display(), e);
+                    skip_failed_registry(opts, summary, &mut failed_packages, &registry_urls[i].0.2, &package_registries, i);
+                    None
+                }
+            }
         })
-    }))?;
+        .collect();
 
+    let rust_version = if opts.ignore_rust_version {
+        None
+    } else {
+        cargo_update::ops::rustc_version()
+    };
+    if !failed_packages.is_empty() {
+        packages.retain(|p| !failed_packages.contains(&(p.name.clone(), p.registry.clone())));
+    }
+    let mut offline_unavailable = BTreeSet::new();
     for package in &mut packages {
-        let registry_idx = match registries.iter().position(|(.., pkg_names)| pkg_names.contains(&package.name)) {
-            Some(i) => i,
+        let registry_idx = match package_registries.get(&(package.name.clone(), package.registry.clone())) {
+            Some(&i) => i,
             None => {
                 panic!("Couldn't find registry for package {} (please report to http://github.com/nabijaczleweli/cargo-update)",
                        &package.name[..])
             }
         };
 
-        let install_prereleases = configuration.get(&package.name).and_then(|c| c.install_prereleases);
-        package.pull_version(&latest_registries[registry_idx], &registry_repos[registry_idx], install_prereleases);
+        match latest_registries[registry_idx].as_ref() {
+            Some(latest) if !opts.offline || package.has_version_data(latest, &registry_repos[registry_idx]) => {
+                let install_prereleases = configuration.get(&package.name).and_then(|c| c.install_prereleases);
+                package.pull_version(latest, &registry_repos[registry_idx], install_prereleases, rust_version.as_ref());
+            }
+            _ => {
+                offline_unavailable.insert(package.name.clone());
+            }
+        }
+    }
+    if let Some(max_behind) = opts.max_versions_behind {
+        packages.retain(|p| p.versions_behind.map(|b| b > max_behind).unwrap_or(false));
     }
 
-    if !opts.quiet {
-        let mut out = TabWriter::new(stdout());
-        writeln!(out, "Package\tInstalled\tLatest\tNeeds update").unwrap();
-        for (package, package_target_version, package_install_prereleases) in
-            {
-                let mut pkgs = packages.iter()
-                    .map(|p| {
-                        let cfg = configuration.get(&p.name);
-                        (p, cfg.as_ref().and_then(|c| c.target_version.as_ref()), cfg.as_ref().and_then(|c| c.install_prereleases))
-                    })
-                    .collect::<Vec<_>>();
-                pkgs.sort_by(|&(ref lhs, lhstv, lhsip), &(ref rhs, rhstv, rhsip)| {
-                    (!lhs.needs_update(lhstv, lhsip, opts.downdate), &lhs.name).cmp(&(!rhs.needs_update(rhstv, rhsip, opts.downdate), &rhs.name))
-                });
-                pkgs
-            } {
+    let mut plan = cargo_update::ops::plan_updates(&packages, &configuration, opts.downdate, opts.force);
+    if opts.filter.iter().any(polled_filter) {
+        let keep: BTreeSet<_> =
+            plan.packages.iter().filter(|p| opts.filter.iter().all(|f| f.matches_polled(p.needs_update))).map(|p| p.name.clone()).collect();
+        packages.retain(|p| keep.contains(&p.name));
+        plan.packages.retain(|p| keep.contains(&p.name));
+    }
+    if opts.force_rebuild {
+        plan.rebuild_up_to_date();
+    }
+    for p in plan.packages.iter_mut().filter(|p| held(&p.name)) {
+        p.update = false;
+    }
+
+    if opts.quiet == 0 {
+        let mut out = TabWriter::new(stdout()).ansi(opts.color);
+        let mut any_breaking = false;
+        writeln!(out, "Package\tInstalled\tLatest\tNeeds update{}", if opts.verbose { "\tWhy" } else { "" }).unwrap();
+        for package in {
+            let mut pkgs = plan.packages.iter().collect::<Vec<_>>();
+            pkgs.sort_by(|lhs, rhs| (!lhs.needs_update, &lhs.name).cmp(&(!rhs.needs_update, &rhs.name)));
+            pkgs
+        } {
             write!(out, "{}\t", package.name).unwrap();
 
             if let Some(ref v) = package.version {
                 write!(out, "v{}", v).unwrap();
+                if package.current_yanked {
+                    write!(out, " (yanked)").unwrap();
+                }
             } else {
                 write!(out, "No").unwrap();
             }
 
-            if let Some(tv) = package_target_version {
+            if offline_unavailable.contains(&package.name) {
+                write!(out, "\tN/A (offline)").unwrap();
+            } else if let Some(ref tv) = package.target_version {
                 write!(out, "\t{}", tv).unwrap();
-            } else if let Some(upd_v) = package.update_to_version() {
-                write!(out, "\tv{}", upd_v).unwrap();
+            } else if let Some(ref upd_v) = package.update_to_version {
+                any_breaking |= package.breaking;
+                if package.breaking {
+                    write!(out, "\t{}", paint(opts, YELLOW, format!("v{}!", upd_v))).unwrap();
+                } else {
+                    write!(out, "\tv{}", upd_v).unwrap();
+                }
                 if let Some(alt_v) = package.alternative_version.as_ref() {
                     write!(out, " (v{} available)", alt_v).unwrap();
                 }
+                if package.installed_newer && !package.current_yanked {
+                    write!(out, " (installed is newer)").unwrap();
+                }
             } else {
                 write!(out, "\tN/A").unwrap();
             }
 
-            writeln!(out,
-                     "\t{}",
-                     if package.needs_update(package_target_version, package_install_prereleases, opts.downdate) {
-                         "Yes"
-                     } else {
-                         "No"
-                     })
+            write!(out,
+                   "\t{}",
+                   if package.needs_update {
+                       paint(opts, GREEN, "Yes")
+                   } else if package.update {
+                       paint(opts, None, "Rebuild")
+                   } else {
+                       paint(opts, None, "No")
+                   })
                 .unwrap();
+            if held(&package.name) {
+                write!(out, " (held)").unwrap();
+            }
+            if opts.verbose {
+                if offline_unavailable.contains(&package.name) {
+                    write!(out, "\tnot cached for offline use").unwrap();
+                } else {
+                    write!(out, "\t{}", package.status).unwrap();
+                }
+            }
+            writeln!(out).unwrap();
+        }
+        for package in &path_packages {
+            writeln!(out, "{}\tv{}\tN/A (path)\tNo", package.name, package.version).unwrap();
         }
         writeln!(out).unwrap();
+        if any_breaking {
+            writeln!(out, "! marks semver-incompatible updates, which may break workflows.").unwrap();
+            writeln!(out).unwrap();
+        }
         out.flush().unwrap();
     }
 
-    let mut success_global = vec![];
-    let mut errored_global = vec![];
-    let mut result_global = None;
+    if opts.update {
+        let git_names = installed_git_packages.iter()
+            .filter(|_| opts.update_git)
+            .filter(|p| opts.prefixes.is_empty() || cargo_update::ops::package_name_has_prefix(&p.name, &opts.prefixes))
+            .filter(|p| opts.all || to_update.iter().any(|u| p.name == u.0) || opts.from_snapshot.contains_key(&p.name))
+            .filter(|p| !held(&p.name))
+            .map(|p| &p.name[..]);
+        let path_names = path_packages.iter().filter(|_| opts.path_reinstall).map(|p| &p.name[..]);
+        check_toolchains(opts,
+                         &configuration,
+                         plan.packages.iter().filter(|p| p.update).map(|p| &p.name[..]).chain(git_names).chain(path_names));
+    }
+
+    let cargo_update::ops::UpdateSummary { updated: success_global, failed: errored_global, up_to_date } = summary;
+    // Only skipped registry errors so far; then the first failure of the registry, git, and path packages,
+    // which never stops the rest from being updated
+    let mut result_global = errored_global.first().map(|&(_, code)| code);
+    // (name, old version, new version) of the packages updated successfully
+    let mut version_changes = vec![];
+    // (name, how long cargo install took) in installation order
+    let install_times = Mutex::new(vec![]);
+    // Wall-clock time of the install runs; less than the sum of install_times when installing in parallel
+    let mut install_elapsed = Duration::ZERO;
 
     if opts.update {
-        if !opts.force {
-            packages.retain(|p| {
-                let cfg = configuration.get(&p.name);
-                p.needs_update(cfg.as_ref().and_then(|c| c.target_version.as_ref()),
-                               cfg.as_ref().and_then(|c| c.install_prereleases),
-                               opts.downdate)
-            });
-        }
+        up_to_date.extend(plan.packages.iter().filter(|p| !p.needs_update && !p.update && !opts.force).map(|p| p.name.clone()));
+        let mut packages: Vec<_> = packages.into_iter().zip(&plan.packages).filter(|(_, planned)| planned.update).map(|(p, _)| p).collect();
+        // Installed on its own after everything else, so the executable swap doesn't race other installs
+        let self_package = if opts.self_update {
+            packages.iter().position(|p| p.name == "cargo-update").map(|i| packages.remove(i))
+        } else {
+            None
+        };
+        let package_count = packages.len() + self_package.is_some() as usize;
 
-        packages.retain(|pkg| pkg.update_to_version().is_some());
+        if package_count != 0 {
+            if !confirm_update(opts, package_count, "") {
+                return Ok(());
+            }
+            run_pre_command(opts, updating, package_count)?;
 
-        if !packages.is_empty() {
-            let (success, errored, result): (Vec<String>, Vec<String>, Option<i32>) = packages.into_iter()
-                .map(|package| -> (String, Result<(), i32>) {
-                    if !opts.quiet {
-                        println!("{} {}",
-                                 if package.version.is_some() {
-                                     "Updating"
-                                 } else {
-                                     "Installing"
-                                 },
-                                 package.name);
+            let registry_names: BTreeMap<_, _> = package_registries.iter()
+                .map(|(pkg, &i)| {
+                    let (registry_url, _, registry_name) = &registry_urls[i].0;
+                    (pkg, (registry_url, registry_name))
+                })
+                .collect();
+            // None if skipped because the package has no binaries
+            let install_package = |package: cargo_update::ops::RegistryPackage, buffered: bool| -> (String, Option<Result<(), i32>>) {
+                let mut buf = if buffered && opts.quiet == 0 {
+                    Some((vec![], vec![]))
+                } else {
+                    None
+                };
+                if opts.quiet == 0 {
+                    let verb = match package.version.as_ref() {
+                        Some(v) if Some(v) == package.update_to_version() => "Rebuilding",
+                        Some(_) => "Updating",
+                        None => "Installing",
+                    };
+                    match buf.as_mut() {
+                        Some((out, _)) => writeln!(out, "{} {}", verb, package.name).unwrap(),
+                        None => info!("{} {}", verb, package.name),
                     }
+                }
 
-                    if cfg!(target_os = "windows") && package.version.is_some() && package.name == "cargo-update" {
-                        save_cargo_update_exec(package.version.as_ref().unwrap());
-                    }
+                if cfg!(target_os = "windows") && !opts.dry_run && package.version.is_some() && package.name == "cargo-update" {
+                    save_cargo_update_exec(package.version.as_ref().unwrap());
+                }
 
-                    let registry_name = match registry_urls.iter().find(|(_, pkg_names)| pkg_names.contains(&package.name)) {
-                        Some(u) => &(u.0).2,
-                        None => {
-                            panic!("Couldn't find registry URL for package {} (please report to http://github.com/nabijaczleweli/cargo-update)",
-                                   &package.name[..])
-                        }
-                    };
-                    let install_res = {
-                            let cfg = configuration.get(&package.name);
-                            if opts.install_cargo == None && registry_name == "crates-io" && opts.cargo_install_args.is_empty() &&
-                               (cfg == None || cfg == Some(&Default::default())) {
-                                    Command::new("cargo-binstall")
-                                        .arg("--roots")
-                                        .arg(&opts.cargo_dir.0)
-                                        .arg("--no-confirm")
-                                        .arg("--version")
-                                        .arg(&format!("={}", package.update_to_version().unwrap()))
-                                        .arg("--force")
-                                        .args(if opts.quiet { Some("--quiet") } else { None })
-                                        .args(if opts.locked { Some("--locked") } else { None })
-                                        .arg(&package.name)
-                                        .status()
-                                } else {
-                                    Err(IoErrorKind::NotFound.into())
+                let (registry_url, registry_name) = match registry_names.get(&(package.name.clone(), package.registry.clone())) {
+                    Some(&r) => r,
+                    None => {
+                        panic!("Couldn't find registry URL for package {} (please report to http://github.com/nabijaczleweli/cargo-update)",
+                               &package.name[..])
+                    }
+                };
+                let install_start = Instant::now();
+                let install_res = {
+                        let cfg = run_config(opts, &package.name, configuration.get(&package.name));
+                        let cfg = cfg.as_deref();
+                        match opts.binstall.as_ref() {
+                            Some(binstall) if opts.install_cargo == None && !opts.offline && registry_name == "crates-io" &&
+                                              opts.cargo_install_args.is_empty() &&
+                                              (cfg == None || cfg == Some(&Default::default())) => {
+                                let mut cmd = Command::new(binstall);
+                                // cargo-binstall has no -j, but passes the environment down to cargo install when it has to build
+                                if let Some(ref j) = opts.jobs.as_ref() {
+                                    cmd.env("CARGO_BUILD_JOBS", j);
                                 }
-                                .or_else(|_| if let Some(cfg) = cfg {
-                                    let mut cmd = Command::new(&opts.install_cargo.as_deref().unwrap_or(OsStr::new("cargo")));
-                                    cfg.environmentalise(&mut cmd)
-                                        .args(cfg.cargo_args(&package.executables).iter().map(AsRef::as_ref))
-                                        .arg("--root")
-                                        .arg(&opts.cargo_dir.0)
-                                        .args(if opts.quiet { Some("--quiet") } else { None })
-                                        .args(if opts.locked { Some("--locked") } else { None })
-                                        .arg("--version")
-                                        .arg(if let Some(tv) = cfg.target_version.as_ref() {
-                                            tv.to_string()
-                                        } else {
-                                            package.update_to_version().unwrap().to_string()
-                                        })
-                                        .arg("--registry")
-                                        .arg(registry_name.as_ref());
-                                    if let Some(ref j) = opts.jobs.as_ref() {
-                                        cmd.arg("-j").arg(j);
-                                    }
-                                    cmd.arg(&package.name)
-                                        .args(&opts.cargo_install_args)
-                                        .status()
-                                } else {
-                                    let mut cmd = Command::new(&opts.install_cargo.as_deref().unwrap_or(OsStr::new("cargo")));
-                                    cmd.arg("install")
-                                        .arg("--root")
-                                        .arg(&opts.cargo_dir.0)
-                                        .arg("-f")
-                                        .args(if opts.quiet { Some("--quiet") } else { None })
-                                        .args(if opts.locked { Some("--locked") } else { None })
-                                        .arg("--version")
-                                        .arg(package.update_to_version().unwrap().to_string())
-                                        .arg("--registry")
-                                        .arg(registry_name.as_ref());
-                                    if let Some(ref j) = opts.jobs.as_ref() {
-                                        cmd.arg("-j").arg(j);
-                                    }
-                                    cmd.arg(&package.name)
-                                        .args(&opts.cargo_install_args)
-                                        .status()
-                                })
+                                run_command(cmd.envs(target_dir_env(opts))
+                                    .arg("--roots")
+                                    .arg(&opts.cargo_dir.0)
+                                    .arg("--no-confirm")
+                                    .arg("--version")
+                                    .arg(&format!("={}", package.update_to_version().unwrap()))
+                                    .arg("--force")
+                                    .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                                    .args(if opts.locked { Some("--locked") } else { None })
+                                    .arg(&package.name),
+                                            buf.as_mut(),
+                                            opts.dry_run)
+                            }
+                            _ => Err(IoErrorKind::NotFound.into()),
                         }
-                        .unwrap();
+                            .or_else(|_| if let Some(cfg) = cfg {
+                                let mut cmd = Command::new(install_cargo(opts, Some(cfg)));
+                                cfg.environmentalise(cmd.envs(target_dir_env(opts)))
+                                    .args(default_toolchain(opts, Some(cfg)))
+                                    .args(cfg.cargo_args(&package.executables).iter().map(AsRef::as_ref))
+                                    .arg("--root")
+                                    .arg(&opts.cargo_dir.0)
+                                    .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                                    .args(if opts.locked { Some("--locked") } else { None })
+                                    .args(if opts.offline { Some("--offline") } else { None })
+                                    .arg("--version")
+                                    .arg(if let Some(tv) = cfg.target_version.as_ref() {
+                                        tv.to_string()
+                                    } else {
+                                        package.update_to_version().unwrap().to_string()
+                                    })
+                                    .arg("--registry")
+                                    .arg(registry_name.as_ref());
+                                // Configured jobs are in cargo_args()
+                                if let Some(ref j) = opts.jobs.as_ref().filter(|_| cfg.jobs.is_none()) {
+                                    cmd.arg("-j").arg(j);
+                                }
+                                run_command(cmd.arg(&package.name).args(&opts.cargo_install_args), buf.as_mut(), opts.dry_run)
+                            } else {
+                                let mut cmd = Command::new(install_cargo(opts, None));
+                                cmd.envs(target_dir_env(opts))
+                                    .args(default_toolchain(opts, None))
+                                    .arg("install")
+                                    .arg("--root")
+                                    .arg(&opts.cargo_dir.0)
+                                    .arg("-f")
+                                    .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                                    .args(if opts.locked { Some("--locked") } else { None })
+                                    .args(if opts.offline { Some("--offline") } else { None })
+                                    .arg("--version")
+                                    .arg(package.update_to_version().unwrap().to_string())
+                                    .arg("--registry")
+                                    .arg(registry_name.as_ref());
+                                if let Some(ref j) = opts.jobs.as_ref() {
+                                    cmd.arg("-j").arg(j);
+                                }
+                                run_command(cmd.arg(&package.name).args(&opts.cargo_install_args), buf.as_mut(), opts.dry_run)
+                            })
+                    }
+                    .unwrap();
+                install_times.lock().unwrap().push((package.name.clone(), install_start.elapsed()));
+                let post_install_res = if install_res.success() {
+                    run_post_install(opts,
+                                     configuration.get(&package.name),
+                                     &package.name,
+                                     package.update_to_version().unwrap(),
+                                     buf.as_mut())
+                } else {
+                    Ok(())
+                };
 
-                    if !opts.quiet {
-                        println!();
+                match buf {
+                    Some((mut out, err)) => {
+                        writeln!(out).unwrap();
+                        let mut stdout = stdout().lock();
+                        let mut stderr = stderr().lock();
+                        stderr.write_all(&err).unwrap();
+                        stdout.write_all(&out).unwrap();
+                        stdout.flush().unwrap();
+                    }
+                    None if opts.quiet == 0 => info!(""),
+                    None => {}
+                }
+                if !install_res.success() {
+                    if cfg!(target_os = "windows") && package.version.is_some() && package.name == "cargo-update" {
+                        restore_cargo_update_exec(package.version.as_ref().unwrap());
                     }
-                    if !install_res.success() {
-                        if cfg!(target_os = "windows") && package.version.is_some() && package.name == "cargo-update" {
-                            restore_cargo_update_exec(package.version.as_ref().unwrap());
-                        }
 
-                        (package.name, Err(install_res.code().unwrap_or(-1)))
-                    } else {
-                        (package.name, Ok(()))
+                    // cargo install refuses libraries, but only after extracting them, so check what it got
+                    if cargo_update::ops::registry_package_has_binaries(&opts.cargo_dir.1,
+                                                                        registry_url,
+                                                                        &package.name,
+                                                                        package.update_to_version().unwrap()) == Some(false) {
+                        if opts.quiet < 2 {
+                            warn!("{} has no binaries, skipping.", package.name);
+                        }
+                        return (package.name, None);
                     }
-                })
-                .fold((vec![], vec![], None), |(mut s, mut e, r), (pn, p)| match p {
-                    Ok(()) => {
+
+                    (package.name, Some(Err(install_res.code().unwrap_or(-1))))
+                } else {
+                    (package.name, Some(post_install_res))
+                }
+            };
+
+            let install_phase_start = Instant::now();
+            let mut results: Vec<_> = if opts.jobs_packages > 1 && packages.len() > 1 {
+                let queue = Mutex::new(packages.into_iter().enumerate());
+                let results = Mutex::new(vec![]);
+                thread::scope(|s| for _ in 0..opts.jobs_packages {
+                    s.spawn(|| loop {
+                        let next = queue.lock().unwrap().next();
+                        match next {
+                            Some((i, package)) => {
+                                let res = install_package(package, true);
+                                results.lock().unwrap().push((i, res));
+                            }
+                            None => break,
+                        }
+                    });
+                });
+
+                let mut results = results.into_inner().unwrap();
+                results.sort_by_key(|&(i, _)| i);
+                results.into_iter().map(|(_, res)| res).collect()
+            } else {
+                packages.into_iter().map(|package| install_package(package, false)).collect()
+            };
+            results.extend(self_package.map(|package| install_package(package, false)));
+            install_elapsed += install_phase_start.elapsed();
+
+            let (success, errored, no_binaries, result): (Vec<String>, Vec<(String, i32)>, Vec<String>, Option<i32>) = results.into_iter()
+                .fold((vec![], vec![], vec![], None), |(mut s, mut e, mut nb, r), (pn, p)| match p {
+                    Some(Ok(())) => {
                         s.push(pn);
-                        (s, e, r)
+                        (s, e, nb, r)
                     }
-                    Err(pr) => {
-                        e.push(pn);
-                        (s, e, r.or_else(|| Some(pr)))
+                    Some(Err(pr)) => {
+                        e.push((pn, pr));
+                        (s, e, nb, r.or_else(|| Some(pr)))
+                    }
+                    None => {
+                        nb.push(pn);
+                        (s, e, nb, r)
                     }
                 });
 
-            if !opts.quiet {
-                println!();
-                println!("Updated {} package{}.", success.len(), if success.len() == 1 { "" } else { "s" });
+            if opts.quiet == 0 {
+                info!("");
+                info!("{}", paint(opts, GREEN, format!("Updated {} package{}.", success.len(), if success.len() == 1 { "" } else { "s" })));
+                if !no_binaries.is_empty() {
+                    info!("Skipped {} (no binaries).", no_binaries.join(", "));
+                }
             }
-            success_global = success;
+            version_changes.extend(plan.packages.iter().filter(|p| success.contains(&p.name)).map(|p| {
+                (p.name.clone(),
+                 p.version.as_ref().map(|v| format!("v{}", v)).unwrap_or_else(|| "none".to_string()),
+                 match (p.target_version.as_ref(), p.update_to_version.as_ref()) {
+                     (Some(tv), _) => tv.to_string(),
+                     (None, Some(upd_v)) => format!("v{}", upd_v),
+                     (None, None) => "?".to_string(),
+                 })
+            }));
+            *success_global = success;
 
             if !errored.is_empty() && result.is_some() {
-                eprint!("Failed to update ");
-                for (i, e) in errored.iter().enumerate() {
-                    if i != 0 {
-                        eprint!(", ");
-                    }
-                    eprint!("{}", e);
+                if opts.quiet < 2 {
+                    error!("{} {}.", paint(opts, RED, "Failed to update"), errored.iter().map(|e| &e.0[..]).collect::<Vec<_>>().join(", "));
+                    error!("");
                 }
-                eprintln!(".");
-                eprintln!();
 
-                if opts.update_git {
-                    errored_global = errored;
+                errored_global.extend(errored);
+                if result_global.is_none() {
                     result_global = result;
-                } else {
-                    return Err(result.unwrap());
                 }
             }
         } else {
-            if !opts.quiet {
-                println!("No packages need updating.");
+            if opts.quiet == 0 {
+                info!("No packages need updating.");
             }
         }
     }
@@ -348,41 +876,73 @@ fn actual_main() -> Result<(), i32> {
     if opts.update_git {
         let mut packages = installed_git_packages;
 
+        for (name, package) in &opts.from_snapshot {
+            if let (Some(url), Some(rev)) = (package.git.as_ref(), package.rev.as_ref()) {
+                if opts.quiet < 2 && !packages.iter().any(|p| p.name == *name) {
+                    warn!("Warning: git package {} from the snapshot isn't installed; install it with cargo install --git {} --rev {} {}.",
+                              name,
+                              url,
+                              rev,
+                              name);
+                }
+            }
+        }
+        if !opts.prefixes.is_empty() {
+            packages.retain(|p| cargo_update::ops::package_name_has_prefix(&p.name, &opts.prefixes));
+        }
         if !opts.filter.is_empty() {
-            packages.retain(|p| configuration.get(&p.name).map(|p_cfg| opts.filter.iter().all(|f| f.matches(p_cfg))).unwrap_or(false));
+            packages.retain(|p| {
+                let installed = installed_time(&p.executables);
+                opts.filter
+                    .iter()
+                    .filter(|f| !polled_filter(f))
+                    .all(|f| f.matches_source(cargo_update::ops::PackageSource::Git, configuration.get(&p.name), installed))
+            });
         }
         if opts.update && !opts.all {
-            packages.retain(|p| opts.to_update.iter().any(|u| p.name == u.0));
+            packages.retain(|p| to_update.iter().any(|u| p.name == u.0) || opts.from_snapshot.contains_key(&p.name));
         }
 
         let git_db_dir = crates_file.with_file_name("git").join("db");
-        for package in &mut packages {
-            package.pull_version(&opts.temp_dir,
-                                 &git_db_dir,
-                                 http_proxy.as_ref().map(String::as_str),
-                                 cargo_config.net_git_fetch_with_cli);
+        if !opts.offline {
+            for package in &mut packages {
+                package.pull_version(&opts.temp_dir,
+                                     &git_db_dir,
+                                     http_proxy.as_ref().map(String::as_str),
+                                     cargo_config.net_git_fetch_with_cli,
+                                     opts.git_cli_fallback,
+                                     configuration.get(&package.name).and_then(|c| c.git_ref.as_deref()),
+                                     opts.git_shallow);
+            }
+        }
+        if opts.filter.iter().any(polled_filter) {
+            packages.retain(|p| opts.filter.iter().all(|f| f.matches_polled(p.needs_update())));
         }
 
-        if !opts.quiet {
-            let mut out = TabWriter::new(stdout());
+        if opts.quiet == 0 {
+            let mut out = TabWriter::new(stdout()).ansi(opts.color);
             writeln!(out, "Package\tInstalled\tLatest\tNeeds update").unwrap();
             packages.sort_by(|lhs, rhs| (!lhs.needs_update(), &lhs.name).cmp(&(!rhs.needs_update(), &rhs.name)));
             for package in &packages {
-                struct OidOrError<'a, Oid: Display, GitError: Display>(&'a Result<Oid, GitError>);
+                struct OidOrError<'a, Oid: Display, GitError: Display>(&'a Result<Oid, GitError>, bool);
                 impl<Oid: Display, GitError: Display> Display for OidOrError<'_, Oid, GitError> {
                     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
-                        match self.0 {
-                            Ok(oid) => write!(f, "{}", oid),
-                            Err(err) => write!(f, "git error: {}", err),
+                        match (self.1, self.0) {
+                            (true, _) => write!(f, "N/A (offline)"),
+                            (false, Ok(oid)) => write!(f, "{}", oid),
+                            (false, Err(err)) => write!(f, "git error: {}", err),
                         }
                     }
                 }
+                let describe = |id: git2::Oid| package.describe(&opts.temp_dir, &git_db_dir, id).unwrap_or_else(|| short_oid(id));
                 writeln!(out,
-                         "{}\t{}\t{}\t{}",
+                         "{}\t{}\t{}\t{}{}",
                          package.name,
-                         package.id,
-                         OidOrError(&package.newest_id),
-                         if package.needs_update() { "Yes" } else { "No" })
+                         describe(package.id),
+                         paint(opts, if package.newest_id.is_err() && !opts.offline { RED } else { None },
+                               OidOrError(&package.newest_id.as_ref().map(|&id| describe(id)), opts.offline)),
+                         if package.needs_update() { paint(opts, GREEN, "Yes") } else { paint(opts, None, "No") },
+                         if held(&package.name) { " (held)" } else { "" })
                     .unwrap();
             }
             writeln!(out).unwrap();
@@ -390,44 +950,68 @@ fn actual_main() -> Result<(), i32> {
         }
 
         if opts.update {
-            if !opts.force {
-                packages.retain(cargo_update::ops::GitRepoPackage::needs_update);
+            packages.retain(|p| !held(&p.name));
+            if !opts.force && !opts.force_rebuild {
+                packages.retain(|p| {
+                    let needs_update = p.needs_update();
+                    if !needs_update {
+                        up_to_date.push(p.name.clone());
+                    }
+                    needs_update
+                });
+            }
+            let declined = !packages.is_empty() && !confirm_update(opts, packages.len(), "git ");
+            if declined {
+                packages.clear();
             }
 
             if !packages.is_empty() {
-                let (success, errored, result): (Vec<String>, Vec<String>, Option<i32>) = packages.into_iter()
+                run_pre_command(opts, updating, packages.len())?;
+
+                let ids: BTreeMap<_, _> = packages.iter().map(|p| (p.name.clone(), (p.id, p.newest_id.as_ref().ok().copied()))).collect();
+                let install_phase_start = Instant::now();
+                let (success, errored, result): (Vec<String>, Vec<(String, i32)>, Option<i32>) = packages.into_iter()
                     .map(|package| -> (String, Result<(), i32>) {
-                        if !opts.quiet {
-                            println!("Updating {} from {}", package.name, package.url);
+                        if opts.quiet == 0 {
+                            info!("Updating {} from {}", package.name, package.url);
                         }
 
-                        if cfg!(target_os = "windows") && package.name == "cargo-update" {
+                        if cfg!(target_os = "windows") && !opts.dry_run && package.name == "cargo-update" {
                             save_cargo_update_exec(&package.id.to_string());
                         }
 
-                        let install_res = if let Some(cfg) = configuration.get(&package.name) {
-                                let mut cmd = Command::new(&opts.install_cargo.as_deref().unwrap_or(OsStr::new("cargo")));
-                                cmd.args(cfg.cargo_args(package.executables).iter().map(AsRef::as_ref))
+                        let install_start = Instant::now();
+                        let install_res = if let Some(cfg) = run_config(opts, &package.name, configuration.get(&package.name)).as_deref() {
+                                let mut cmd = Command::new(install_cargo(opts, Some(cfg)));
+                                cfg.environmentalise(cmd.envs(target_dir_env(opts)))
+                                    .args(default_toolchain(opts, Some(cfg)))
+                                    .args(cfg.cargo_args(package.executables).iter().map(AsRef::as_ref))
                                     .arg("--root")
                                     .arg(&opts.cargo_dir.0)
-                                    .args(if opts.quiet { Some("--quiet") } else { None })
+                                    .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                                    .args(if opts.offline { Some("--offline") } else { None })
                                     .arg("--git")
                                     .arg(&package.url)
                                     .arg(&package.name);
-                                if let Some(ref b) = package.branch.as_ref() {
+                                if let Some((ref_arg, git_ref)) = cfg.git_ref_args() {
+                                    cmd.arg(ref_arg).arg(git_ref);
+                                } else if let Some(ref b) = package.branch.as_ref() {
                                     cmd.arg("--branch").arg(b);
                                 }
-                                if let Some(ref j) = opts.jobs.as_ref() {
+                                if let Some(ref j) = opts.jobs.as_ref().filter(|_| cfg.jobs.is_none()) {
                                     cmd.arg("-j").arg(j);
                                 }
-                                cmd.args(&opts.cargo_install_args).status()
+                                run_command(cmd.args(&opts.cargo_install_args), None, opts.dry_run)
                             } else {
-                                let mut cmd = Command::new(&opts.install_cargo.as_deref().unwrap_or(OsStr::new("cargo")));
-                                cmd.arg("install")
+                                let mut cmd = Command::new(install_cargo(opts, None));
+                                cmd.envs(target_dir_env(opts))
+                                    .args(default_toolchain(opts, None))
+                                    .arg("install")
                                     .arg("--root")
                                     .arg(&opts.cargo_dir.0)
                                     .arg("-f")
-                                    .args(if opts.quiet { Some("--quiet") } else { None })
+                                    .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                                    .args(if opts.offline { Some("--offline") } else { None })
                                     .arg("--git")
                                     .arg(&package.url)
                                     .arg(&package.name);
@@ -437,12 +1021,22 @@ fn actual_main() -> Result<(), i32> {
                                 if let Some(ref j) = opts.jobs.as_ref() {
                                     cmd.arg("-j").arg(j);
                                 }
-                                cmd.args(&opts.cargo_install_args).status()
+                                run_command(cmd.args(&opts.cargo_install_args), None, opts.dry_run)
                             }
                             .unwrap();
+                        install_times.lock().unwrap().push((package.name.clone(), install_start.elapsed()));
+                        let post_install_res = if install_res.success() {
+                            run_post_install(opts,
+                                             configuration.get(&package.name),
+                                             &package.name,
+                                             package.newest_id.as_ref().unwrap_or(&package.id),
+                                             None)
+                        } else {
+                            Ok(())
+                        };
 
-                        if !opts.quiet {
-                            println!();
+                        if opts.quiet == 0 {
+                            info!("");
                         }
                         if !install_res.success() {
                             if cfg!(target_os = "windows") && package.name == "cargo-update" {
@@ -451,7 +1045,7 @@ fn actual_main() -> Result<(), i32> {
 
                             (package.name, Err(install_res.code().unwrap_or(-1)))
                         } else {
-                            (package.name, Ok(()))
+                            (package.name, post_install_res)
                         }
                     })
                     .fold((vec![], vec![], None), |(mut s, mut e, r), (pn, p)| match p {
@@ -460,75 +1054,180 @@ fn actual_main() -> Result<(), i32> {
                             (s, e, r)
                         }
                         Err(pr) => {
-                            e.push(pn);
+                            e.push((pn, pr));
                             (s, e, r.or_else(|| Some(pr)))
                         }
                     });
+                install_elapsed += install_phase_start.elapsed();
 
-                if !opts.quiet {
-                    println!();
-                    println!("Updated {} git package{}.", success.len(), if success.len() == 1 { "" } else { "s" });
+                if opts.quiet == 0 {
+                    info!("");
+                    info!("{}", paint(opts, GREEN, format!("Updated {} git package{}.", success.len(), if success.len() == 1 { "" } else { "s" })));
                 }
+                version_changes.extend(success.iter().map(|name| {
+                    let (id, newest_id) = ids[name];
+                    (name.clone(), short_oid(id), newest_id.map(short_oid).unwrap_or_else(|| "?".to_string()))
+                }));
                 success_global.extend(success);
 
                 if !errored.is_empty() && result.is_some() {
-                    eprint!("Failed to update ");
-                    for (i, e) in errored.iter().enumerate() {
-                        if i != 0 {
-                            eprint!(", ");
-                        }
-                        eprint!("{}", e);
+                    if opts.quiet < 2 {
+                        error!("{} {}.", paint(opts, RED, "Failed to update"), errored.iter().map(|e| &e.0[..]).collect::<Vec<_>>().join(", "));
+                        error!("");
                     }
-                    eprintln!(".");
-                    eprintln!();
 
                     errored_global.extend(errored);
-
                     if result_global.is_none() {
-                        return Err(result.unwrap());
+                        result_global = result;
                     }
                 }
-            } else {
-                if !opts.quiet {
-                    println!("No git packages need updating.");
+            } else if opts.quiet == 0 && !declined {
+                info!("No git packages need updating.");
+            }
+        }
+    }
+
+    if opts.update && !path_packages.is_empty() {
+        if opts.path_reinstall {
+            run_pre_command(opts, updating, path_packages.len())?;
+
+            let install_phase_start = Instant::now();
+            let (success, errored, result): (Vec<String>, Vec<(String, i32)>, Option<i32>) = path_packages.into_iter()
+                .map(|package| -> (String, Result<(), i32>) {
+                    if opts.quiet == 0 {
+                        info!("Reinstalling {} from {}", package.name, package.path.display());
+                    }
+
+                    if cfg!(target_os = "windows") && !opts.dry_run && package.name == "cargo-update" {
+                        save_cargo_update_exec(&package.version);
+                    }
+
+                    let cfg = run_config(opts, &package.name, configuration.get(&package.name));
+                    let mut cmd = Command::new(install_cargo(opts, cfg.as_deref()));
+                    cmd.envs(target_dir_env(opts));
+                    if let Some(cfg) = cfg.as_deref() {
+                        cfg.environmentalise(&mut cmd)
+                            .args(default_toolchain(opts, Some(cfg)))
+                            .args(cfg.cargo_args(&package.executables).iter().map(AsRef::as_ref));
+                    } else {
+                        cmd.args(default_toolchain(opts, None)).arg("install").arg("-f");
+                    }
+                    cmd.arg("--root")
+                        .arg(&opts.cargo_dir.0)
+                        .args(if opts.quiet > 0 { Some("--quiet") } else { None })
+                        .args(if opts.locked { Some("--locked") } else { None })
+                        .args(if opts.offline { Some("--offline") } else { None })
+                        .arg("--path")
+                        .arg(&package.path);
+                    if let Some(ref j) = opts.jobs.as_ref().filter(|_| configuration.get(&package.name).and_then(|c| c.jobs).is_none()) {
+                        cmd.arg("-j").arg(j);
+                    }
+                    let install_start = Instant::now();
+                    let install_res = run_command(cmd.args(&opts.cargo_install_args), None, opts.dry_run).unwrap();
+                    install_times.lock().unwrap().push((package.name.clone(), install_start.elapsed()));
+                    let post_install_res = if install_res.success() {
+                        run_post_install(opts, configuration.get(&package.name), &package.name, &package.version, None)
+                    } else {
+                        Ok(())
+                    };
+
+                    if opts.quiet == 0 {
+                        info!("");
+                    }
+                    if !install_res.success() {
+                        if cfg!(target_os = "windows") && package.name == "cargo-update" {
+                            restore_cargo_update_exec(&package.version);
+                        }
+
+                        (package.name, Err(install_res.code().unwrap_or(-1)))
+                    } else {
+                        (package.name, post_install_res)
+                    }
+                })
+                .fold((vec![], vec![], None), |(mut s, mut e, r), (pn, p)| match p {
+                    Ok(()) => {
+                        s.push(pn);
+                        (s, e, r)
+                    }
+                    Err(pr) => {
+                        e.push((pn, pr));
+                        (s, e, r.or_else(|| Some(pr)))
+                    }
+                });
+            install_elapsed += install_phase_start.elapsed();
+
+            if opts.quiet == 0 {
+                info!("");
+                info!("{}", paint(opts, GREEN, format!("Reinstalled {} path package{}.", success.len(), if success.len() == 1 { "" } else { "s" })));
+            }
+            success_global.extend(success);
+
+            if !errored.is_empty() && result.is_some() {
+                if opts.quiet < 2 {
+                    error!("{} {}.", paint(opts, RED, "Failed to reinstall"), errored.iter().map(|e| &e.0[..]).collect::<Vec<_>>().join(", "));
+                    error!("");
+                }
+
+                errored_global.extend(errored);
+                if result_global.is_none() {
+                    result_global = result;
                 }
             }
+        } else if opts.quiet == 0 {
+            info!("Skipping {} package{} installed from a path: {}; pass --path-reinstall to reinstall them.",
+                  path_packages.len(),
+                  if path_packages.len() == 1 { "" } else { "s" },
+                  path_packages.iter().map(|p| &p.name[..]).collect::<Vec<_>>().join(", "));
+            info!("");
         }
     }
 
     if opts.update {
-        if !opts.quiet {
-            print!("Overall updated {} package{}",
-                   success_global.len(),
-                   match success_global.len() {
-                       0 => "s",
-                       1 => ": ",
-                       _ => "s: ",
-                   });
-            for (i, e) in success_global.iter().enumerate() {
-                if i != 0 {
-                    print!(", ");
+        let install_times = install_times.into_inner().unwrap();
+        if opts.quiet == 0 {
+            info!("Overall updated {} package{}{}{}.",
+                  success_global.len(),
+                  match success_global.len() {
+                      0 => "s",
+                      1 => ": ",
+                      _ => "s: ",
+                  },
+                  success_global.join(", "),
+                  if !install_times.is_empty() && !opts.dry_run {
+                      format!(" in {:.1}s", install_elapsed.as_secs_f64())
+                  } else {
+                      String::new()
+                  });
+
+            if !version_changes.is_empty() {
+                info!("");
+                for (name, old, new) in &version_changes {
+                    info!("{}: {} -> {}", name, old, new);
+                }
+            }
+
+            if !install_times.is_empty() && !opts.dry_run {
+                info!("");
+                let mut out = TabWriter::new(stdout());
+                for (name, took) in &install_times {
+                    writeln!(out, "{}\t{:.1}s", name, took.as_secs_f64()).unwrap();
                 }
-                print!("{}", e);
+                out.flush().unwrap();
             }
-            println!(".");
         }
 
         if !errored_global.is_empty() && result_global.is_some() {
-            eprint!("Overall failed to update {} package{}",
-                    errored_global.len(),
-                    match errored_global.len() {
-                        0 => "s",
-                        1 => ": ",
-                        _ => "s: ",
-                    });
-            for (i, e) in errored_global.iter().enumerate() {
-                if i != 0 {
-                    eprint!(", ");
-                }
-                eprint!("{}", e);
+            if opts.quiet < 2 {
+                error!("{} {} package{}{}.",
+                       paint(opts, RED, "Overall failed to update"),
+                       errored_global.len(),
+                       match errored_global.len() {
+                           0 => "s",
+                           1 => ": ",
+                           _ => "s: ",
+                       },
+                       errored_global.iter().map(|e| &e.0[..]).collect::<Vec<_>>().join(", "));
             }
-            eprintln!(".");
 
             return Err(result_global.unwrap());
         }
@@ -537,6 +1236,393 @@ fn actual_main() -> Result<(), i32> {
     Ok(())
 }
 
+/// Count the packages from a registry that couldn't be updated as failed, so the ones from the other registries still are
+fn skip_failed_registry(opts: &cargo_update::Options, summary: &mut cargo_update::ops::UpdateSummary, failed_packages: &mut BTreeSet<(String, String)>,
+                        registry: &str, package_registries: &BTreeMap<(String, String), usize>, registry_idx: usize) {
+    let skipped: Vec<_> = package_registries.iter().filter(|&(_, &i)| i == registry_idx).map(|(pkg, _)| pkg).collect();
+    if opts.quiet < 2 {
+        warn!("Warning: skipping {}, since {} couldn't be updated.",
+              skipped.iter().map(|(pn, _)| &pn[..]).collect::<Vec<_>>().join(", "),
+              registry);
+    }
+    summary.failed.extend(skipped.iter().map(|(pn, _)| (pn.clone(), 2)));
+    failed_packages.extend(skipped.into_iter().cloned());
+}
+
+/// Where registry update progress goes: stdout, or, with the logging feature, `debug!()`
+#[cfg(not(feature = "logging"))]
+fn progress_output() -> Box<dyn Write> {
+    Box::new(stdout())
+}
+
+#[cfg(feature = "logging")]
+fn progress_output() -> Box<dyn Write> {
+    Box::new(cargo_update::ops::LogWriter::new(log::Level::Debug))
+}
+
+/// Messages through the log crate to stdout, or stderr for warnings and errors, like without the logging feature
+#[cfg(feature = "logging")]
+struct StdLogger;
+
+#[cfg(feature = "logging")]
+impl log::Log for StdLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.level() <= log::Level::Warn {
+            eprintln!("{}", record.args());
+        } else {
+            println!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {
+        let _ = stdout().flush();
+    }
+}
+
+fn short_oid(id: git2::Oid) -> String {
+    let mut id = id.to_string();
+    id.truncate(7);
+    id
+}
+
+const RED: Option<&str> = Some("31");
+const GREEN: Option<&str> = Some("32");
+const YELLOW: Option<&str> = Some("33");
+
+/// Displays as the wrapped value, in the SGR colour, if any, when colouring output.
+struct Painted<T: Display>(T, Option<&'static str>);
+
+impl<T: Display> Display for Painted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.1 {
+            Some(colour) => write!(f, "\x1b[{}m{}\x1b[0m", colour, self.0),
+            None => self.0.fmt(f),
+        }
+    }
+}
+
+fn paint<T: Display>(opts: &cargo_update::Options, colour: Option<&'static str>, what: T) -> Painted<T> {
+    Painted(what, colour.filter(|_| opts.color))
+}
+
+/// The cargo to install the package with: the one configured for it, then `--install-cargo`, then plain `cargo`.
+fn install_cargo<'o>(opts: &'o cargo_update::Options, cfg: Option<&'o cargo_update::ops::PackageConfig>) -> &'o OsStr {
+    cfg.and_then(|c| c.install_cargo.as_deref())
+        .map(Path::as_os_str)
+        .or(opts.install_cargo.as_deref())
+        .unwrap_or(OsStr::new("cargo"))
+}
+
+/// The `+toolchain` argument for the toolchain from `rust-toolchain`, if any, unless the package has one configured.
+fn default_toolchain(opts: &cargo_update::Options, cfg: Option<&cargo_update::ops::PackageConfig>) -> Option<String> {
+    match (opts.rust_toolchain.as_ref(), cfg.and_then(|cfg| cfg.toolchain.as_ref())) {
+        (Some(toolchain), None) => Some(format!("+{}", toolchain)),
+        _ => None,
+    }
+}
+
+/// Warn about the toolchains configured for the packages about to be built that rustup doesn't have installed.
+///
+/// Nothing to check if there's no rustup, or to say with -qq.
+fn check_toolchains<'n, I: IntoIterator<Item = &'n str>>(opts: &cargo_update::Options, configuration: &BTreeMap<String, cargo_update::ops::PackageConfig>,
+                                                         names: I) {
+    let mut configured = BTreeMap::<_, Vec<_>>::new();
+    for name in names {
+        if let Some(toolchain) = configuration.get(name).and_then(|c| c.toolchain.as_ref()).filter(|t| !t.is_empty()) {
+            configured.entry(&toolchain[..]).or_default().push(name);
+        }
+    }
+    if configured.is_empty() || opts.quiet >= 2 || !cargo_update::ops::program_exists(OsStr::new("rustup"), env::var_os("PATH").as_deref()) {
+        return;
+    }
+
+    if let Some(installed) = cargo_update::ops::rustup_toolchains() {
+        configured.retain(|toolchain, _| !cargo_update::ops::toolchain_installed(toolchain, &installed));
+        for (toolchain, names) in &configured {
+            warn!("Warning: toolchain {} (for {}) isn't installed; install it with rustup toolchain install {}.",
+                      toolchain,
+                      names.join(", "),
+                      toolchain);
+        }
+        if !configured.is_empty() {
+            warn!("");
+        }
+    }
+}
+
+/// `CARGO_TARGET_DIR` for `cargo install`, from `--target-dir` or the environment; set before the package's environment,
+/// so its overrides win.
+fn target_dir_env(opts: &cargo_update::Options) -> Option<(&'static str, &Path)> {
+    opts.target_dir.as_ref().map(|td| ("CARGO_TARGET_DIR", td.as_path()))
+}
+
+/// The package's configuration with `--with-feature`, `--without-default-features`,
+/// and the manifest's `locked = true` applied on top, just for this run.
+fn run_config<'c>(opts: &cargo_update::Options, name: &str, cfg: Option<&'c cargo_update::ops::PackageConfig>)
+                  -> Option<Cow<'c, cargo_update::ops::PackageConfig>> {
+    let locked = opts.locked_packages.contains(name);
+    if opts.run_config.is_empty() && !locked {
+        return cfg.map(Cow::Borrowed);
+    }
+
+    let mut cfg = cfg.cloned().unwrap_or_default();
+    cfg.execute_operations(&opts.run_config);
+    if locked {
+        cfg.execute_operations(&[cargo_update::ops::ConfigOperation::SetEnforceLock(true)]);
+    }
+    Some(Cow::Owned(cfg))
+}
+
+/// List the git clones left behind by packages that aren't installed anymore, with their sizes, removing them if `remove`.
+///
+/// Clones in cargo's git db that cargo-update never polled may be other projects' git dependencies, so those are only listed.
+fn gc_git_clones(opts: &cargo_update::Options, crates_file: &Path, remove: bool) -> Result<(), i32> {
+    let orphans = cargo_update::ops::orphaned_git_clones(&opts.temp_dir,
+                                                         &crates_file.with_file_name("git").join("db"),
+                                                         &cargo_update::ops::installed_git_repo_packages(crates_file));
+    if orphans.is_empty() {
+        if opts.quiet == 0 {
+            info!("No stale git clones.");
+            info!("");
+        }
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    let (mut cleaned, mut freed, mut kept) = (0, 0, 0);
+    let mut out = TabWriter::new(stdout());
+    for (orphan, ours) in &orphans {
+        let size = cargo_update::ops::dir_size(orphan);
+        if !ours {
+            kept += 1;
+            if opts.quiet == 0 {
+                writeln!(out, "{}\t{}\t(not polled by cargo-update, kept)", orphan.display(), human_size(size)).unwrap();
+            }
+            continue;
+        }
+        if remove {
+            if let Err(e) = fs::remove_dir_all(orphan) {
+                error!("Couldn't remove {}: {}.", orphan.display(), e);
+                result = Err(2);
+                continue;
+            }
+        }
+        cleaned += 1;
+        freed += size;
+        if opts.quiet == 0 {
+            writeln!(out, "{}\t{}", orphan.display(), human_size(size)).unwrap();
+        }
+    }
+    if opts.quiet == 0 {
+        out.flush().unwrap();
+        if remove {
+            info!("Removed {} stale git clone{}, freeing {}.", cleaned, if cleaned == 1 { "" } else { "s" }, human_size(freed));
+        } else {
+            info!("Removing {} stale git clone{} with -y would free {}.", cleaned, if cleaned == 1 { "" } else { "s" }, human_size(freed));
+        }
+        if kept != 0 {
+            info!("Kept {} git clone{} cargo-update didn't poll, since other projects may depend on {}.",
+                  kept,
+                  if kept == 1 { "" } else { "s" },
+                  if kept == 1 { "it" } else { "them" });
+        }
+        info!("");
+    }
+    result
+}
+
+/// Lock the install root against other cargo-update runs updating it at the same time, waiting for them with `--wait`.
+///
+/// The lock is released when the returned file is closed, or when cargo-update exits in any way, since it's an advisory
+/// `flock()`/`LockFileEx()` lock; the lock file itself is left in place, as removing it would race with other runs opening it.
+fn lock_install_root(opts: &cargo_update::Options, crates_file: &Path) -> Result<File, i32> {
+    let lock_file = crates_file.with_file_name(".cargo-update.lock");
+    let lock = OpenOptions::new().create(true).write(true).open(&lock_file).map_err(|e| {
+            error!("Couldn't open {}: {}.", lock_file.display(), e);
+            3
+        })?;
+    match lock.try_lock_exclusive() {
+        Ok(()) => Ok(lock),
+        Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+            if !opts.wait {
+                error!("Another cargo-update is updating packages in {}; pass --wait to wait for it to finish.",
+                       lock_file.parent().unwrap_or(Path::new(".")).display());
+                return Err(3);
+            }
+            if opts.quiet < 2 {
+                warn!("Waiting for another cargo-update updating packages in {} to finish...",
+                      lock_file.parent().unwrap_or(Path::new(".")).display());
+            }
+            lock.lock_exclusive().map(|_| lock).map_err(|e| {
+                error!("Couldn't lock {}: {}.", lock_file.display(), e);
+                3
+            })
+        }
+        Err(e) => {
+            error!("Couldn't lock {}: {}.", lock_file.display(), e);
+            Err(3)
+        }
+    }
+}
+
+/// Print each package's executables (only the named ones, if any), and whether they're still in the cargo bin directory.
+fn list_executables(opts: &cargo_update::Options, crates_file: &Path) {
+    if opts.quiet != 0 {
+        return;
+    }
+
+    let installed = cargo_update::ops::InstalledPackages::read(crates_file);
+    let mut packages: Vec<_> = installed.registry
+        .into_iter()
+        .map(|p| (p.name, p.executables))
+        .chain(installed.git.into_iter().map(|p| (p.name, p.executables)))
+        .chain(installed.path.into_iter().map(|p| (p.name, p.executables)))
+        .filter(|(name, _)| opts.to_update.is_empty() || opts.to_update.iter().any(|u| cargo_update::ops::package_name_glob_matches(name, &u.0)))
+        .filter(|(name, _)| opts.prefixes.is_empty() || cargo_update::ops::package_name_has_prefix(name, &opts.prefixes))
+        .collect();
+    packages.sort();
+
+    let bin_dir = crates_file.with_file_name("bin");
+    let mut out = TabWriter::new(stdout()).ansi(opts.color);
+    writeln!(out, "Package\tExecutable\tInstalled").unwrap();
+    for (name, executables) in &packages {
+        if executables.is_empty() {
+            writeln!(out, "{}\tN/A\t", name).unwrap();
+        }
+        for (i, exe) in executables.iter().enumerate() {
+            writeln!(out,
+                     "{}\t{}\t{}",
+                     if i == 0 { &name[..] } else { "" },
+                     exe,
+                     if bin_dir.join(exe).exists() {
+                         paint(opts, None, "Yes")
+                     } else {
+                         paint(opts, RED, "Missing")
+                     })
+                .unwrap();
+        }
+    }
+    writeln!(out).unwrap();
+    out.flush().unwrap();
+}
+
+/// The size in bytes as B, KiB, MiB, or GiB.
+fn human_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{} B", size),
+        1024..=0xFFFFF => format!("{:.1} KiB", size as f64 / 1024.0),
+        0x100000..=0x3FFFFFFF => format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0)),
+        _ => format!("{:.1} GiB", size as f64 / (1024.0 * 1024.0 * 1024.0)),
+    }
+}
+
+/// Ask whether to update this many packages if `--confirm` was specified, and stdin is a terminal; an empty answer is yes.
+fn confirm_update(opts: &cargo_update::Options, packages: usize, kind: &str) -> bool {
+    if !opts.confirm || opts.quiet > 0 || !stdin().is_terminal() {
+        return true;
+    }
+
+    print!("Update {} {}package{}? [Y/n] ", packages, kind, if packages == 1 { "" } else { "s" });
+    let _ = stdout().flush();
+    let mut answer = String::new();
+    match stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => matches!(&answer.trim().to_ascii_lowercase()[..], "" | "y" | "yes"),
+    }
+}
+
+/// Run the pre-command, if any, unless it was already run; remembering that updating is starting with this many packages.
+fn run_pre_command(opts: &cargo_update::Options, updating: &mut Option<usize>, packages: usize) -> Result<(), i32> {
+    if updating.is_some() || opts.dry_run {
+        return Ok(());
+    }
+
+    if let Some(pre_command) = opts.pre_command.as_ref() {
+        let status = cargo_update::ops::shell_command(pre_command, &opts.temp_dir)
+            .env("CARGO_UPDATE_PACKAGES", packages.to_string())
+            .status()
+            .map_err(|e| {
+                error!("Running pre-command: {}", e);
+                -1
+            })?;
+        if !status.success() {
+            error!("Pre-command failed, not updating.");
+            return Err(status.code().unwrap_or(-1));
+        }
+    }
+
+    *updating = Some(packages);
+    Ok(())
+}
+
+/// Run the package's post-install command, if configured, with `$CARGO_UPDATE_PACKAGE` and `$CARGO_UPDATE_VERSION` set.
+fn run_post_install<V: Display>(opts: &cargo_update::Options, cfg: Option<&cargo_update::ops::PackageConfig>, package: &str, version: V,
+                                buf: Option<&mut (Vec<u8>, Vec<u8>)>)
+                                -> Result<(), i32> {
+    let post_install = match cfg.and_then(|c| c.post_install.as_ref()) {
+        Some(post_install) => post_install,
+        None => return Ok(()),
+    };
+
+    let status = run_command(cargo_update::ops::shell_command(OsStr::new(post_install), &opts.temp_dir)
+                                 .env("CARGO_UPDATE_PACKAGE", package)
+                                 .env("CARGO_UPDATE_VERSION", version.to_string()),
+                             buf,
+                             opts.dry_run)
+        .map_err(|e| {
+            error!("Running post-install command for {}: {}", package, e);
+            -1
+        })?;
+    if !status.success() {
+        error!("Post-install command for {} failed.", package);
+        return Err(status.code().unwrap_or(-1));
+    }
+    Ok(())
+}
+
+/// Run the command, capturing its output into the `(stdout, stderr)` buffer, if any, instead of inheriting the standard streams.
+///
+/// If `dry_run`, print the command line there instead and pretend it succeeded, if the program exists.
+fn run_command(cmd: &mut Command, buf: Option<&mut (Vec<u8>, Vec<u8>)>, dry_run: bool) -> io::Result<ExitStatus> {
+    if dry_run {
+        if !cargo_update::ops::program_exists(cmd.get_program(), env::var_os("PATH").as_deref()) {
+            return Err(IoErrorKind::NotFound.into());
+        }
+        match buf {
+            Some((out, _)) => writeln!(out, "{}", cargo_update::ops::command_line(cmd)).unwrap(),
+            None => info!("{}", cargo_update::ops::command_line(cmd)),
+        }
+        return Ok(success_status());
+    }
+
+    match buf {
+        Some((out, err)) => {
+            cmd.output().map(|o| {
+                out.extend(o.stdout);
+                err.extend(o.stderr);
+                o.status
+            })
+        }
+        None => cmd.status(),
+    }
+}
+
+#[cfg(not(target_os="windows"))]
+fn success_status() -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(0)
+}
+
+#[cfg(target_os="windows")]
+fn success_status() -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(0)
+}
+
 
 /// This way the past-current exec will be "replaced" and we'll get no dupes in .cargo.toml
 #[cfg(target_os="windows")]
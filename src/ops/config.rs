@@ -2,13 +2,14 @@ use std::fmt::{Formatter as FFormatter, Result as FResult, Write as FWrite};
 use serde::{Deserializer, Deserialize, Serializer, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::io::ErrorKind as IoErrorKind;
+use std::path::{PathBuf, Path};
 use json_deserializer as json;
 use std::process::Command;
 use std::default::Default;
 use semver::VersionReq;
 use std::borrow::Cow;
-use std::path::Path;
 use serde::de;
+use std::env;
 use std::fs;
 use toml;
 
@@ -26,12 +27,30 @@ pub enum ConfigOperation {
     AddFeature(String),
     /// Remove the feature from the list of features to compile with.
     RemoveFeature(String),
+    /// Whether to compile the package with all features.
+    SetAllFeatures(bool),
     /// Set build profile (`dev`/`release`/*~/.cargo/config.toml* `[profile.gaming]`/&c.)
     SetBuildProfile(Cow<'static, str>),
+    /// Set the target triple to compile the package for.
+    SetTargetTriple(String),
+    /// Compile the package for the host.
+    RemoveTargetTriple,
+    /// Pass the specified argument to `cargo install`, after all others.
+    AddInstallArg(String),
+    /// Don't pass any additional arguments to `cargo install`.
+    ClearInstallArgs,
+    /// Pin the git package to the specified tag or rev.
+    SetGitRef(String),
+    /// Track the git package's branch.
+    RemoveGitRef,
     /// Set allowing to install prereleases to the specified value.
     SetInstallPrereleases(bool),
     /// Set enforcing Cargo.lock to the specified value.
     SetEnforceLock(bool),
+    /// Set passing `--frozen` (`--locked` and `--offline`) to the specified value.
+    SetFrozen(bool),
+    /// Set passing `--offline` to the specified value.
+    SetOffline(bool),
     /// Set installing only the pre-set binaries.
     SetRespectBinaries(bool),
     /// Constrain the installed to the specified one.
@@ -44,11 +63,37 @@ pub enum ConfigOperation {
     ClearEnvironment(String),
     /// Remove configuration for an environment variable.
     InheritEnvironment(String),
+    /// Run the specified shell command after installing the package.
+    SetPostInstall(String),
+    /// Don't run anything after installing the package.
+    ClearPostInstall,
+    /// Build the package with the specified number of parallel jobs, overriding `-j`.
+    SetJobs(u32),
+    /// Build the package with the default number of parallel jobs.
+    RemoveJobs,
+    /// Set the `RUSTFLAGS` to build the package with, split on whitespace.
+    SetRustflags(String),
+    /// Add a flag to the `RUSTFLAGS` to build the package with.
+    AddRustflag(String),
+    /// Don't set `RUSTFLAGS`.
+    ClearRustflags,
+    /// Whether to append the flags to the inherited `RUSTFLAGS` instead of replacing them.
+    SetAppendRustflags(bool),
+    /// Install the package with the specified cargo executable, overriding `--install-cargo`.
+    SetInstallCargo(String),
+    /// Install the package with the default cargo.
+    RemoveInstallCargo,
+    /// Whether to only update the package if it's named explicitly, not with `--all`.
+    SetHold(bool),
     /// Reset configuration to default values.
     ResetConfig,
 }
 
 
+/// Name of the `.install_config.toml` section every package's configuration is based on.
+pub const DEFAULT_CONFIG_SECTION: &str = "*";
+
+
 /// Compilation configuration for one crate.
 ///
 /// # Examples
@@ -76,21 +121,48 @@ pub struct PackageConfig {
     pub default_features: bool,
     /// Features to compile the package with.
     pub features: BTreeSet<String>,
+    /// Whether to compile the package with all features, overriding `default_features` and `features`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_features: bool,
     /// Equivalent to `build_profile = Some("dev")` but binds stronger
     pub debug: Option<bool>,
     /// The build profile (`test` or `bench` or one from *~/.cargo/config.toml* `[profile.gaming]`); CANNOT be `dev` (`debug =
     /// Some(true)`) or `release` (`debug = build_profile = None`)
     pub build_profile: Option<Cow<'static, str>>,
+    /// Target triple to compile the package for, or `None` for the host.
+    pub target: Option<String>,
+    /// Additional arguments to pass to `cargo install`, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// Tag or rev to pin a git package to, or `None` to track the branch.
+    pub git_ref: Option<String>,
     /// Whether to install pre-release versions.
     pub install_prereleases: Option<bool>,
     /// Whether to enforce Cargo.lock versions.
     pub enforce_lock: Option<bool>,
+    /// Whether to require Cargo.lock to be up to date and not access the network, as with `--frozen`.
+    pub frozen: Option<bool>,
+    /// Whether to not access the network.
+    pub offline: Option<bool>,
     /// Whether to install only the pre-configured binaries.
     pub respect_binaries: Option<bool>,
     /// Versions to constrain to.
     pub target_version: Option<VersionReq>,
     /// Environment variables to alter for cargo. `None` to remove.
     pub environment: Option<BTreeMap<String, EnvironmentOverride>>,
+    /// Shell command to run after successfully installing the package.
+    pub post_install: Option<String>,
+    /// Number of parallel jobs to build the package with, or `None` for the default.
+    pub jobs: Option<u32>,
+    /// Flags to set `RUSTFLAGS` to, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rustflags: Vec<String>,
+    /// Whether to append `rustflags` to the inherited `RUSTFLAGS` instead of replacing them.
+    pub append_rustflags: Option<bool>,
+    /// Cargo executable to install the package with, or `None` for the default.
+    pub install_cargo: Option<PathBuf>,
+    /// Whether to only update the package if it's named explicitly.
+    pub hold: Option<bool>,
     /// Read in from `.crates2.json`, shouldn't be saved
     #[serde(skip)]
     pub from_transient: bool,
@@ -100,13 +172,25 @@ impl PartialEq for PackageConfig {
         self.toolchain /************/ == other.toolchain && // !
         self.default_features /*****/ == other.default_features && // !
         self.features /*************/ == other.features && // !
+        self.all_features /*********/ == other.all_features && // !
         self.debug /****************/ == other.debug && // !
         self.build_profile /********/ == other.build_profile && // !
+        self.target /***************/ == other.target && // !
+        self.extra_args /***********/ == other.extra_args && // !
+        self.git_ref /**************/ == other.git_ref && // !
         self.install_prereleases /**/ == other.install_prereleases && // !
         self.enforce_lock /*********/ == other.enforce_lock && // !
+        self.frozen /***************/ == other.frozen && // !
+        self.offline /**************/ == other.offline && // !
         self.respect_binaries /*****/ == other.respect_binaries && // !
         self.target_version /*******/ == other.target_version && // !
-        self.environment /**********/ == other.environment
+        self.environment /**********/ == other.environment && // !
+        self.post_install /*********/ == other.post_install && // !
+        self.jobs /*****************/ == other.jobs && // !
+        self.rustflags /************/ == other.rustflags && // !
+        self.append_rustflags /*****/ == other.append_rustflags && // !
+        self.install_cargo /********/ == other.install_cargo && // !
+        self.hold /*****************/ == other.hold
         // No from_transient
     }
 }
@@ -144,10 +228,16 @@ impl PackageConfig {
     ///                    feats.insert("rustc-serialize".to_string());
     ///                    feats
     ///                },
+    ///                all_features: false,
     ///                debug: Some(true),
     ///                build_profile: None,
+    ///                target: None,
+    ///                extra_args: vec![],
+    ///                git_ref: None,
     ///                install_prereleases: Some(false),
     ///                enforce_lock: Some(true),
+    ///                frozen: None,
+    ///                offline: None,
     ///                respect_binaries: Some(true),
     ///                target_version: Some(VersionReq::from_str(">=0.1").unwrap()),
     ///                environment: Some({
@@ -156,6 +246,12 @@ impl PackageConfig {
     ///                    vars.insert("CC".to_string(), EnvironmentOverride(None));
     ///                    vars
     ///                }),
+    ///                post_install: None,
+    ///                jobs: None,
+    ///                rustflags: vec![],
+    ///                append_rustflags: None,
+    ///                install_cargo: None,
+    ///                hold: None,
     ///                from_transient: false,
     ///            });
     /// # }
@@ -193,10 +289,12 @@ impl PackageConfig {
         }
         res.push("install".into());
         res.push("-f".into());
-        if !self.default_features {
+        if self.all_features {
+            res.push("--all-features".into());
+        } else if !self.default_features {
             res.push("--no-default-features".into());
         }
-        if !self.features.is_empty() {
+        if !self.features.is_empty() && !self.all_features {
             res.push("--features".into());
             let mut a = String::new();
             for f in &self.features {
@@ -204,8 +302,15 @@ impl PackageConfig {
             }
             res.push(a.into());
         }
-        if let Some(true) = self.enforce_lock {
-            res.push("--locked".into());
+        if let Some(true) = self.frozen {
+            res.push("--frozen".into());
+        } else {
+            if let Some(true) = self.enforce_lock {
+                res.push("--locked".into());
+            }
+            if let Some(true) = self.offline {
+                res.push("--offline".into());
+            }
         }
         if let Some(true) = self.respect_binaries {
             for x in executables {
@@ -227,10 +332,41 @@ impl PackageConfig {
             res.push("--profile".into());
             res.push(prof.clone());
         }
+        if let Some(ref t) = self.target {
+            res.push("--target".into());
+            res.push(t.clone().into());
+        }
+        if let Some(j) = self.jobs {
+            res.push("-j".into());
+            res.push(j.to_string().into());
+        }
+        res.extend(self.extra_args.iter().cloned().map(Cow::from));
         res
     }
 
-    /// Apply transformations from `self.environment` to `cmd`.
+    /// Get the `cargo install --git` argument pinning the package to the configured ref, if any.
+    ///
+    /// Refs that look like (abbreviated) commit hashes are passed as `--rev`, all others as `--tag`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{ConfigOperation, PackageConfig};
+    /// assert_eq!(PackageConfig::from(&[ConfigOperation::SetGitRef("v0.13.2".to_string())]).git_ref_args(),
+    ///            Some(("--tag", "v0.13.2")));
+    /// assert_eq!(PackageConfig::from(&[ConfigOperation::SetGitRef("eb231b3e".to_string())]).git_ref_args(),
+    ///            Some(("--rev", "eb231b3e")));
+    /// assert_eq!(PackageConfig::from(&[]).git_ref_args(), None);
+    /// ```
+    pub fn git_ref_args(&self) -> Option<(&'static str, &str)> {
+        self.git_ref.as_ref().map(|gr| if gr.len() >= 7 && gr.len() <= 40 && gr.bytes().all(|b| b.is_ascii_hexdigit()) {
+            ("--rev", &gr[..])
+        } else {
+            ("--tag", &gr[..])
+        })
+    }
+
+    /// Apply transformations from `self.environment` and `self.rustflags` to `cmd`.
     pub fn environmentalise<'c>(&self, cmd: &'c mut Command) -> &'c mut Command {
         if let Some(env) = self.environment.as_ref() {
             for (var, val) in env {
@@ -241,9 +377,55 @@ impl PackageConfig {
                 };
             }
         }
+        if let Some(rustflags) = self.rustflags_value(env::var("RUSTFLAGS").ok().as_deref()) {
+            cmd.env("RUSTFLAGS", rustflags);
+        }
         cmd
     }
 
+    /// Get the `RUSTFLAGS` to build with, given the inherited ones, or `None` to leave them alone.
+    ///
+    /// When appending, a `RUSTFLAGS` set or cleared in `self.environment` is appended to instead of the inherited one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{ConfigOperation, PackageConfig};
+    /// let cfg = PackageConfig::from(&[ConfigOperation::SetRustflags("-C target-cpu=native".to_string()),
+    ///                                 ConfigOperation::AddRustflag("-Dwarnings".to_string())]);
+    /// assert_eq!(cfg.rustflags_value(Some("-Copt-level=s")).as_deref(), Some("-C target-cpu=native -Dwarnings"));
+    ///
+    /// let cfg = PackageConfig::from(&[ConfigOperation::AddRustflag("-Dwarnings".to_string()),
+    ///                                 ConfigOperation::SetAppendRustflags(true)]);
+    /// assert_eq!(cfg.rustflags_value(Some("-Copt-level=s")).as_deref(), Some("-Copt-level=s -Dwarnings"));
+    /// assert_eq!(cfg.rustflags_value(None).as_deref(), Some("-Dwarnings"));
+    ///
+    /// assert_eq!(PackageConfig::from(&[]).rustflags_value(Some("-Copt-level=s")), None);
+    /// ```
+    pub fn rustflags_value(&self, inherited: Option<&str>) -> Option<String> {
+        if self.rustflags.is_empty() {
+            return None;
+        }
+
+        let mut ret = String::new();
+        if self.append_rustflags.unwrap_or(false) {
+            let inherited = match self.environment.as_ref().and_then(|env| env.get("RUSTFLAGS")) {
+                Some(EnvironmentOverride(val)) => val.as_deref(),
+                None => inherited,
+            };
+            if let Some(inherited) = inherited.map(str::trim).filter(|i| !i.is_empty()) {
+                ret.push_str(inherited);
+            }
+        }
+        for flag in &self.rustflags {
+            if !ret.is_empty() {
+                ret.push(' ');
+            }
+            ret.push_str(flag);
+        }
+        Some(ret)
+    }
+
     /// Modify `self` according to the specified set of operations.
     ///
     /// If this config was transient (read in from `.crates2.json`), it is made real and will be saved.
@@ -266,13 +448,25 @@ impl PackageConfig {
     ///         feats.insert("rustc-serialize".to_string());
     ///         feats
     ///     },
+    ///     all_features: false,
     ///     debug: None,
     ///     build_profile: None,
+    ///     target: None,
+    ///     extra_args: vec![],
+    ///     git_ref: None,
     ///     install_prereleases: None,
     ///     enforce_lock: None,
+    ///     frozen: None,
+    ///     offline: None,
     ///     respect_binaries: None,
     ///     target_version: Some(VersionReq::from_str(">=0.1").unwrap()),
     ///     environment: None,
+    ///     post_install: None,
+    ///     jobs: None,
+    ///     rustflags: vec![],
+    ///     append_rustflags: None,
+    ///     install_cargo: None,
+    ///     hold: None,
     ///     from_transient: false,
     /// };
     /// cfg.execute_operations(&[ConfigOperation::RemoveToolchain,
@@ -289,13 +483,25 @@ impl PackageConfig {
     ///                    feats.insert("serde".to_string());
     ///                    feats
     ///                },
+    ///                all_features: false,
     ///                debug: Some(true),
     ///                build_profile: None,
+    ///                target: None,
+    ///                extra_args: vec![],
+    ///                git_ref: None,
     ///                install_prereleases: None,
     ///                enforce_lock: None,
+    ///                frozen: None,
+    ///                offline: None,
     ///                respect_binaries: None,
     ///                target_version: None,
     ///                environment: None,
+    ///                post_install: None,
+    ///                jobs: None,
+    ///                rustflags: vec![],
+    ///                append_rustflags: None,
+    ///                install_cargo: None,
+    ///                hold: None,
     ///                from_transient: false,
     ///            });
     /// # }
@@ -307,6 +513,95 @@ impl PackageConfig {
         }
     }
 
+    /// Modify the configurations of all the specified packages in the configset with the specified operations.
+    ///
+    /// Packages without a configuration get a fresh one; ones left at the defaults are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{ConfigOperation, PackageConfig};
+    /// # use std::collections::BTreeMap;
+    /// let mut configuration = BTreeMap::new();
+    /// configuration.insert("cargo-update".to_string(), PackageConfig::from(&[ConfigOperation::SetEnforceLock(true)]));
+    ///
+    /// PackageConfig::execute_operations_on(&mut configuration,
+    ///                                      ["cargo-update", "racer", "treesize"].iter().cloned(),
+    ///                                      &[ConfigOperation::SetToolchain("nightly".to_string())]);
+    /// assert_eq!(configuration.len(), 3);
+    /// assert!(configuration.values().all(|cfg| cfg.toolchain.as_deref() == Some("nightly")));
+    /// assert_eq!(configuration["cargo-update"].enforce_lock, Some(true));
+    ///
+    /// PackageConfig::execute_operations_on(&mut configuration, ["racer", "treesize"].iter().cloned(), &[ConfigOperation::RemoveToolchain]);
+    /// assert_eq!(configuration.keys().collect::<Vec<_>>(), ["cargo-update"]);
+    /// ```
+    pub fn execute_operations_on<'p, P: IntoIterator<Item = &'p str>>(configuration: &mut BTreeMap<String, PackageConfig>, packages: P,
+                                                                   ops: &[ConfigOperation]) {
+        for package in packages {
+            if *configuration.entry(package.to_string())
+                .and_modify(|cfg| cfg.execute_operations(ops))
+                .or_insert_with(|| PackageConfig::from(ops)) == Default::default() {
+                configuration.remove(package);
+            }
+        }
+    }
+
+    /// Get the configuration from basing this one on the specified one.
+    ///
+    /// Values set here win, features, install arguments, and environment variables are combined,
+    /// and default features are disabled if either disables them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{ConfigOperation, PackageConfig};
+    /// let base = PackageConfig::from(&[ConfigOperation::SetToolchain("nightly".to_string()),
+    ///                                  ConfigOperation::AddFeature("serde".to_string()),
+    ///                                  ConfigOperation::SetEnvironment("RUSTC_WRAPPER".to_string(), "sccache".to_string())]);
+    /// let cfg = PackageConfig::from(&[ConfigOperation::SetToolchain("stable".to_string()),
+    ///                                 ConfigOperation::AddFeature("log".to_string())]);
+    ///
+    /// let merged = cfg.inherit(&base);
+    /// assert_eq!(merged.toolchain.as_deref(), Some("stable"));
+    /// assert_eq!(merged.features.iter().collect::<Vec<_>>(), ["log", "serde"]);
+    /// assert!(merged.environment.unwrap().contains_key("RUSTC_WRAPPER"));
+    /// ```
+    pub fn inherit(&self, base: &PackageConfig) -> PackageConfig {
+        let (debug, build_profile) = if self.debug.is_none() && self.build_profile.is_none() {
+            (base.debug, base.build_profile.clone())
+        } else {
+            (self.debug, self.build_profile.clone())
+        };
+        PackageConfig {
+            toolchain: self.toolchain.clone().or_else(|| base.toolchain.clone()),
+            default_features: self.default_features && base.default_features,
+            features: base.features.union(&self.features).cloned().collect(),
+            all_features: self.all_features || base.all_features,
+            debug,
+            build_profile,
+            target: self.target.clone().or_else(|| base.target.clone()),
+            extra_args: base.extra_args.iter().chain(&self.extra_args).cloned().collect(),
+            git_ref: self.git_ref.clone().or_else(|| base.git_ref.clone()),
+            install_prereleases: self.install_prereleases.or(base.install_prereleases),
+            enforce_lock: self.enforce_lock.or(base.enforce_lock),
+            frozen: self.frozen.or(base.frozen),
+            offline: self.offline.or(base.offline),
+            respect_binaries: self.respect_binaries.or(base.respect_binaries),
+            target_version: self.target_version.clone().or_else(|| base.target_version.clone()),
+            environment: match (&base.environment, &self.environment) {
+                (Some(base_env), Some(env)) => Some(base_env.iter().chain(env).map(|(k, v)| (k.clone(), v.clone())).collect()),
+                (base_env, env) => env.clone().or_else(|| base_env.clone()),
+            },
+            post_install: self.post_install.clone().or_else(|| base.post_install.clone()),
+            jobs: self.jobs.or(base.jobs),
+            rustflags: base.rustflags.iter().chain(&self.rustflags).cloned().collect(),
+            append_rustflags: self.append_rustflags.or(base.append_rustflags),
+            install_cargo: self.install_cargo.clone().or_else(|| base.install_cargo.clone()),
+            hold: self.hold.or(base.hold),
+            from_transient: self.from_transient,
+        }
+    }
+
     fn execute_operation(&mut self, op: &ConfigOperation) {
         match op {
             ConfigOperation::SetToolchain(ref tchn) => self.toolchain = Some(tchn.clone()),
@@ -318,13 +613,22 @@ impl PackageConfig {
             ConfigOperation::RemoveFeature(ref feat) => {
                 self.features.remove(feat);
             }
+            ConfigOperation::SetAllFeatures(af) => self.all_features = *af,
             ConfigOperation::SetBuildProfile(d) => {
                 self.debug = None;
                 self.build_profile = Some(d.clone());
                 self.normalise();
             }
+            ConfigOperation::SetTargetTriple(ref tt) => self.target = Some(tt.clone()),
+            ConfigOperation::RemoveTargetTriple => self.target = None,
+            ConfigOperation::AddInstallArg(ref arg) => self.extra_args.push(arg.clone()),
+            ConfigOperation::ClearInstallArgs => self.extra_args.clear(),
+            ConfigOperation::SetGitRef(ref gr) => self.git_ref = Some(gr.clone()),
+            ConfigOperation::RemoveGitRef => self.git_ref = None,
             ConfigOperation::SetInstallPrereleases(pr) => self.install_prereleases = Some(*pr),
             ConfigOperation::SetEnforceLock(el) => self.enforce_lock = Some(*el),
+            ConfigOperation::SetFrozen(f) => self.frozen = Some(*f),
+            ConfigOperation::SetOffline(o) => self.offline = Some(*o),
             ConfigOperation::SetRespectBinaries(rb) => self.respect_binaries = Some(*rb),
             ConfigOperation::SetTargetVersion(ref vr) => self.target_version = Some(vr.clone()),
             ConfigOperation::RemoveTargetVersion => self.target_version = None,
@@ -337,6 +641,17 @@ impl PackageConfig {
             ConfigOperation::InheritEnvironment(ref var) => {
                 self.environment.get_or_insert(Default::default()).remove(var);
             }
+            ConfigOperation::SetPostInstall(ref cmd) => self.post_install = Some(cmd.clone()),
+            ConfigOperation::ClearPostInstall => self.post_install = None,
+            ConfigOperation::SetJobs(j) => self.jobs = Some(*j),
+            ConfigOperation::RemoveJobs => self.jobs = None,
+            ConfigOperation::SetRustflags(ref flags) => self.rustflags = flags.split_whitespace().map(str::to_string).collect(),
+            ConfigOperation::AddRustflag(ref flag) => self.rustflags.push(flag.clone()),
+            ConfigOperation::ClearRustflags => self.rustflags.clear(),
+            ConfigOperation::SetAppendRustflags(a) => self.append_rustflags = Some(*a),
+            ConfigOperation::SetInstallCargo(ref ic) => self.install_cargo = Some(PathBuf::from(ic)),
+            ConfigOperation::RemoveInstallCargo => self.install_cargo = None,
+            ConfigOperation::SetHold(h) => self.hold = Some(*h),
             ConfigOperation::ResetConfig => *self = Default::default(),
         }
     }
@@ -344,10 +659,15 @@ impl PackageConfig {
     /// Read a configset from the specified file, or from the given `.cargo2.json`.
     ///
     /// The first file (usually `.install_config.toml`) is used by default for each package;
-    /// `.cargo2.json`, if any, is used to backfill existing data from cargo.
+    /// `.cargo2.json`, if any, is used to backfill existing data from cargo
+    /// for registry, git, and path packages without a configuration,
+    /// so they're reinstalled with the features and profile they were installed with.
     ///
     /// If the specified file doesn't exist an empty configset is returned.
     ///
+    /// Every package's configuration is based on the one in the [`DEFAULT_CONFIG_SECTION`](constant.DEFAULT_CONFIG_SECTION.html),
+    /// which is kept as-is; use [`read_unmerged()`](#method.read_unmerged) to get the configurations as written.
+    ///
     /// # Examples
     ///
     /// ```
@@ -374,19 +694,47 @@ impl PackageConfig {
     ///             feats.insert("serde".to_string());
     ///             feats
     ///         },
+    ///         all_features: false,
     ///         debug: None,
     ///         build_profile: None,
+    ///         target: None,
+    ///         extra_args: vec![],
+    ///         git_ref: None,
     ///         install_prereleases: None,
     ///         enforce_lock: None,
+    ///         frozen: None,
+    ///         offline: None,
     ///         respect_binaries: None,
     ///         target_version: None,
     ///         environment: None,
+    ///         post_install: None,
+    ///         jobs: None,
+    ///         rustflags: vec![],
+    ///         append_rustflags: None,
+    ///         install_cargo: None,
+    ///         hold: None,
     ///         from_transient: false,
     ///     });
     ///     pkgs
     /// }));
     /// ```
     pub fn read(p: &Path, cargo2_json: &Path) -> Result<BTreeMap<String, PackageConfig>, (String, i32)> {
+        let mut configuration = PackageConfig::read_unmerged(p, cargo2_json)?;
+        if let Some(defaults) = configuration.get(DEFAULT_CONFIG_SECTION).cloned() {
+            for (k, v) in &mut configuration {
+                if k != DEFAULT_CONFIG_SECTION {
+                    *v = v.inherit(&defaults);
+                }
+            }
+        }
+        Ok(configuration)
+    }
+
+    /// Like [`read()`](#method.read), but doesn't base the configurations on the
+    /// [`DEFAULT_CONFIG_SECTION`](constant.DEFAULT_CONFIG_SECTION.html).
+    ///
+    /// This is what should be modified and [`write()`](#method.write)n back.
+    pub fn read_unmerged(p: &Path, cargo2_json: &Path) -> Result<BTreeMap<String, PackageConfig>, (String, i32)> {
         let mut base = match fs::read_to_string(p) {
             Ok(s) => toml::from_str(&s).map_err(|e| (e.to_string(), 2))?,
             Err(e) if e.kind() == IoErrorKind::NotFound => BTreeMap::new(),
@@ -413,7 +761,9 @@ impl PackageConfig {
                 if let Some(json::Value::Object(installs)) = cargo2.remove("installs") {
                     for (k, v) in installs {
                         if let json::Value::Object(v) = v {
-                            if let Some((name, _, _)) = super::parse_registry_package_ident(&k).or_else(|| super::parse_git_package_ident(&k)) {
+                            if let Some((name, _, _)) = super::parse_registry_package_ident(&k)
+                                .or_else(|| super::parse_git_package_ident(&k))
+                                .or_else(|| super::parse_path_package_ident(&k)) {
                                 if !base.contains_key(name) {
                                     base.insert(name.to_string(), PackageConfig::cargo2_package_config(v));
                                 }
@@ -466,12 +816,16 @@ impl PackageConfig {
                 })
                 .collect();
         }
-        // Nothing to parse "all_features" into
+        if let Some(json::Value::Bool(af)) = blob.get("all_features") {
+            ret.all_features = *af;
+        }
         if let Some(json::Value::String(prof)) = blob.get("profile") {
             ret.build_profile = Some(prof.clone().into_owned().into());
         }
+        // "target" is always recorded, even for the host, so passing it back would be redundant at best
         // Nothing to parse PackageConfig::install_prereleases from
         // Nothing to parse PackageConfig::enforce_lock from
+        // Nor PackageConfig::frozen and PackageConfig::offline
         // "bins" is kinda like PackageConfig::respect_binaries but no really
         // "version_req" is set by cargo install --version, so we'd lock after the first update if we parsed it like this
         // Nothing to parse PackageConfig::environment from
@@ -501,13 +855,25 @@ impl PackageConfig {
     ///             feats.insert("serde".to_string());
     ///             feats
     ///         },
+    ///         all_features: false,
     ///         debug: None,
     ///         build_profile: None,
+    ///         target: None,
+    ///         extra_args: vec![],
+    ///         git_ref: None,
     ///         install_prereleases: None,
     ///         enforce_lock: None,
+    ///         frozen: None,
+    ///         offline: None,
     ///         respect_binaries: None,
     ///         target_version: None,
     ///         environment: None,
+    ///         post_install: None,
+    ///         jobs: None,
+    ///         rustflags: vec![],
+    ///         append_rustflags: None,
+    ///         install_cargo: None,
+    ///         hold: None,
     ///         from_transient: false,
     ///     });
     ///     pkgs
@@ -529,13 +895,25 @@ impl Default for PackageConfig {
             toolchain: None,
             default_features: true,
             features: BTreeSet::new(),
+            all_features: false,
             debug: None,
             build_profile: None,
+            target: None,
+            extra_args: vec![],
+            git_ref: None,
             install_prereleases: None,
             enforce_lock: None,
+            frozen: None,
+            offline: None,
             respect_binaries: None,
             target_version: None,
             environment: None,
+            post_install: None,
+            jobs: None,
+            rustflags: vec![],
+            append_rustflags: None,
+            install_cargo: None,
+            hold: None,
             from_transient: false,
         }
     }
@@ -0,0 +1,74 @@
+use std::fmt::{Formatter as FFormatter, Result as FResult, Display};
+use std::error::Error as StdError;
+
+
+/// Why a registry lookup or fetch failed.
+///
+/// Every kind carries the full human-readable message, which is what's `Display`ed.
+///
+/// # Examples
+///
+/// ```
+/// # use cargo_update::ops::Error;
+/// let err = Error::PackageMissing("package checksums-nonexistent doesn't exist: HTTP 404".to_string());
+/// assert_eq!(err.to_string(), "package checksums-nonexistent doesn't exist: HTTP 404");
+/// assert_eq!(err.message(), "package checksums-nonexistent doesn't exist: HTTP 404");
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Error {
+    /// Reading a file or writing progress failed.
+    Io(String),
+    /// A cargo config file isn't TOML, or has values of the wrong type.
+    Toml(String),
+    /// A registry's package data couldn't be parsed.
+    Parse(String),
+    /// Talking to a registry failed, or it responded with an unexpected error.
+    Network(String),
+    /// The registry couldn't be found in, or isn't usable according to, the cargo config.
+    RegistryNotFound(String),
+    /// The registry doesn't have the package.
+    PackageMissing(String),
+    /// The registry refused the request (HTTP 401 or 403).
+    Auth(String),
+}
+
+impl Error {
+    /// The message, whatever the kind.
+    pub fn message(&self) -> &str {
+        match *self {
+            Error::Io(ref msg) |
+            Error::Toml(ref msg) |
+            Error::Parse(ref msg) |
+            Error::Network(ref msg) |
+            Error::RegistryNotFound(ref msg) |
+            Error::PackageMissing(ref msg) |
+            Error::Auth(ref msg) => msg,
+        }
+    }
+
+    /// Rewrite the message, keeping the kind.
+    pub(crate) fn map_message<F: FnOnce(String) -> String>(self, f: F) -> Error {
+        match self {
+            Error::Io(msg) => Error::Io(f(msg)),
+            Error::Toml(msg) => Error::Toml(f(msg)),
+            Error::Parse(msg) => Error::Parse(f(msg)),
+            Error::Network(msg) => Error::Network(f(msg)),
+            Error::RegistryNotFound(msg) => Error::RegistryNotFound(f(msg)),
+            Error::PackageMissing(msg) => Error::PackageMissing(f(msg)),
+            Error::Auth(msg) => Error::Auth(f(msg)),
+        }
+    }
+
+    /// A `Network` error `what: e`, for the setup and running of transfers.
+    pub(crate) fn network<E: Display>(what: &'static str) -> impl FnOnce(E) -> Error {
+        move |e| Error::Network(format!("{}: {}", what, e))
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut FFormatter) -> FResult {
+        f.write_str(self.message())
+    }
+}
+
+impl StdError for Error {}
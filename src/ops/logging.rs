@@ -0,0 +1,70 @@
+use std::io::{self, Write};
+use std::mem;
+use log;
+
+
+/// An output for [`update_index()`](fn.update_index.html) and the like that logs each line through the `log` crate.
+///
+/// Carriage returns (from progress counters) end a line too, and whatever's left is logged when it's dropped;
+/// flushing doesn't log anything, so the dots of sparse fetch progress end up on one line.
+///
+/// Only with the `logging` feature.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate cargo_update;
+/// # extern crate log;
+/// # use cargo_update::ops::LogWriter;
+/// # use std::io::Write;
+/// # fn main() {
+/// let mut out = LogWriter::new(log::Level::Debug);
+/// write!(out, "    Polling registry 'https://index.crates.io/'").unwrap();
+/// out.flush().unwrap();
+/// writeln!(out, "...").unwrap();  // debug!("    Polling registry 'https://index.crates.io/'...")
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LogWriter {
+    /// The level to log at.
+    pub level: log::Level,
+    line: Vec<u8>,
+}
+
+impl LogWriter {
+    /// Log lines written to this at the specified level.
+    pub fn new(level: log::Level) -> LogWriter {
+        LogWriter {
+            level,
+            line: vec![],
+        }
+    }
+
+    fn log_line(&mut self) {
+        if !self.line.is_empty() {
+            log::log!(target: "cargo_update", self.level, "{}", String::from_utf8_lossy(&mem::take(&mut self.line)));
+        }
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &b in buf {
+            match b {
+                b'\n' | b'\r' => self.log_line(),
+                b => self.line.push(b),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.log_line();
+    }
+}
@@ -0,0 +1,142 @@
+use super::{GitRepoPackage, RegistryPackage, PackageConfig};
+use std::collections::{BTreeMap, BTreeSet};
+use semver::Version as Semver;
+use std::path::Path;
+use std::fs;
+use toml;
+
+
+/// The exact state of an installed package, as recorded by `--snapshot` and restored by `--from-snapshot`.
+///
+/// Registry packages have a `version` and `registry`, git packages a `git` URL and `rev`ision (the installed commit).
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPackage {
+    /// The installed version of a registry package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The registry a registry package was installed from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// The repository a git package was installed from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The commit a git package was installed from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The features the package was compiled with, other than the default ones.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub features: BTreeSet<String>,
+    /// The toolchain the package is configured to be compiled with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+}
+
+impl SnapshotPackage {
+    /// Record the installed packages, with the features and toolchains from their configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{RegistryPackage, SnapshotPackage};
+    /// # use std::collections::BTreeMap;
+    /// let packages = [RegistryPackage::parse("checksums 0.5.2 (registry+https://github.com/rust-lang/crates.io-index)", vec![]).unwrap()];
+    /// let snapshot = SnapshotPackage::record(&packages, &[], &BTreeMap::new());
+    /// assert_eq!(snapshot["checksums"].version, Some("0.5.2".to_string()));
+    /// assert_eq!(snapshot["checksums"].registry, Some("https://github.com/rust-lang/crates.io-index".to_string()));
+    /// ```
+    pub fn record(registry_packages: &[RegistryPackage], git_packages: &[GitRepoPackage], configuration: &BTreeMap<String, PackageConfig>)
+                  -> BTreeMap<String, SnapshotPackage> {
+        let configured = |name: &str| {
+            configuration.get(name)
+                .map(|cfg| (cfg.features.clone(), cfg.toolchain.clone()))
+                .unwrap_or_default()
+        };
+
+        registry_packages.iter()
+            .flat_map(|p| p.version.as_ref().map(|v| (p, v)))
+            .map(|(p, version)| {
+                let (features, toolchain) = configured(&p.name);
+                (p.name.clone(),
+                 SnapshotPackage {
+                     version: Some(version.to_string()),
+                     registry: Some(p.registry.clone()),
+                     git: None,
+                     rev: None,
+                     features,
+                     toolchain,
+                 })
+            })
+            .chain(git_packages.iter().map(|p| {
+                let (features, toolchain) = configured(&p.name);
+                (p.name.clone(),
+                 SnapshotPackage {
+                     version: None,
+                     registry: None,
+                     git: Some(p.url.clone()),
+                     rev: Some(p.id.to_string()),
+                     features,
+                     toolchain,
+                 })
+            }))
+            .collect()
+    }
+
+    /// Parse a snapshot, checking that every package is either a registry one with a valid version or a git one with a revision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::SnapshotPackage;
+    /// let snapshot = SnapshotPackage::parse("[checksums]\n\
+    ///                                        version = \"0.5.2\"\n\
+    ///                                        registry = \"https://github.com/rust-lang/crates.io-index\"\n\
+    ///                                        features = [\"cli\"]\n").unwrap();
+    /// assert_eq!(snapshot["checksums"].version, Some("0.5.2".to_string()));
+    /// assert!(snapshot["checksums"].features.contains("cli"));
+    ///
+    /// assert!(SnapshotPackage::parse("[checksums]\nversion = \"latest\"\n").is_err());
+    /// assert!(SnapshotPackage::parse("[alacritty]\ngit = \"https://github.com/jwilm/alacritty\"\n").is_err());
+    /// ```
+    pub fn parse(snapshot: &str) -> Result<BTreeMap<String, SnapshotPackage>, String> {
+        let snapshot: BTreeMap<String, SnapshotPackage> = toml::from_str(snapshot).map_err(|e| e.to_string())?;
+        for (name, package) in &snapshot {
+            match (package.version.as_ref(), package.git.as_ref(), package.rev.as_ref()) {
+                (Some(version), None, None) => {
+                    Semver::parse(version).map_err(|e| format!("{}: version {} invalid: {}", name, version, e))?;
+                }
+                (None, Some(_), Some(_)) => {}
+                _ => return Err(format!("{}: need either a version, or a git URL and rev", name)),
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Read a snapshot from a file, as parsed by `parse()`.
+    pub fn read(p: &Path) -> Result<BTreeMap<String, SnapshotPackage>, String> {
+        SnapshotPackage::parse(&fs::read_to_string(p).map_err(|e| e.to_string())?)
+    }
+
+    /// Write the snapshot to a file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use cargo_update::ops::{RegistryPackage, SnapshotPackage};
+    /// # use std::collections::BTreeMap;
+    /// # use std::env::temp_dir;
+    /// # use std::fs;
+    /// # let td = temp_dir().join("cargo_update-doctest").join("SnapshotPackage-write-0");
+    /// # let _ = fs::create_dir_all(&td);
+    /// let snapshot_file = td.join("tools.toml");
+    /// let packages = [RegistryPackage::parse("checksums 0.5.2 (registry+https://github.com/rust-lang/crates.io-index)", vec![]).unwrap()];
+    /// SnapshotPackage::write(&SnapshotPackage::record(&packages, &[], &BTreeMap::new()), &snapshot_file).unwrap();
+    ///
+    /// assert_eq!(&fs::read_to_string(&snapshot_file).unwrap(),
+    ///            "[checksums]\n\
+    ///             version = \"0.5.2\"\n\
+    ///             registry = \"https://github.com/rust-lang/crates.io-index\"\n");
+    /// ```
+    pub fn write(snapshot: &BTreeMap<String, SnapshotPackage>, p: &Path) -> Result<(), String> {
+        fs::write(p, &toml::to_string(snapshot).map_err(|e| e.to_string())?).map_err(|e| e.to_string())
+    }
+}
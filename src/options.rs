@@ -12,24 +12,29 @@
 //! ```
 
 
-use self::super::ops::{PackageFilterElement, ConfigOperation};
+use self::super::ops::{DEFAULT_CONFIG_SECTION, PackageFilterElement, ConfigOperation, SnapshotPackage, parse_version_spec, manifest_packages,
+                       manifest_locked_packages, find_rust_toolchain, PackageSpec};
 use semver::{VersionReq as SemverReq, Version as Semver};
 use clap::{self, AppSettings, SubCommand, App, Arg};
 use std::ffi::{OsString, OsStr};
+use std::collections::{BTreeMap, BTreeSet};
 use array_tool::vec::Uniq;
+use std::io::{BufReader, IsTerminal, BufRead, stdout, stdin};
 use std::fmt::Arguments;
 use std::process::exit;
-use std::path::PathBuf;
+use std::path::{PathBuf, Path};
 use std::str::FromStr;
-use std::{env, fs};
+use std::fs::{self, File};
+use std::env;
 use home;
+use url::Url;
 
 
 /// Representation of the application's all configurable values.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Options {
-    /// (Additional) packages to update. Default: `[]`
-    pub to_update: Vec<(String, Option<Semver>, String)>,
+    /// (Additional) packages to update, as (name, version requirement, registry, whether the version is an exact pin). Default: `[]`
+    pub to_update: Vec<PackageSpec>,
     /// Whether to update all packages. Default: `false`
     pub all: bool,
     /// Whether to update packages or just list them. Default: `true`
@@ -38,16 +43,29 @@ pub struct Options {
     pub install: bool,
     /// Update all packages. Default: `false`
     pub force: bool,
+    /// Also reinstall packages that don't need updating, without downdating or going past their `target_version`. Default: `false`
+    pub force_rebuild: bool,
     /// Downdate packages to match newest unyanked registry version.
     pub downdate: bool,
     /// Update git packages too (it's expensive). Default: `false`
     pub update_git: bool,
-    /// Don't output messages and pass --quiet to `cargo` subprocesses. Default: `false`
-    pub quiet: bool,
+    /// Don't output status messages (at `1`), nor warnings and failure summaries (at `2`),
+    /// and pass --quiet to `cargo` subprocesses (at either). Default: `0`
+    pub quiet: u64,
+    /// Show why each registry package does or doesn't need updating. Default: `false`
+    pub verbose: bool,
     /// Enforce packages' embedded `Cargo.lock`. Exactly like `CARGO_INSTALL_OPTS=--locked` (or `--enforce-lock` per package) except doesn't disable cargo-binstall. Default: `false`
     pub locked: bool,
     /// Update all packages. Default: empty
     pub filter: Vec<PackageFilterElement>,
+    /// Only consider packages whose names start with one of these; implies `all`. Default: empty
+    pub prefixes: Vec<String>,
+    /// Don't consider registry packages from these registries, by cargo name or URL. Default: empty
+    pub exclude_registries: Vec<String>,
+    /// Only consider registry packages from these registries, by cargo name or URL, if any. Default: empty
+    pub only_registries: Vec<String>,
+    /// Only consider packages more than this many releases behind. Default: `None`
+    pub max_versions_behind: Option<usize>,
     /// The `cargo` home directory; (original, canonicalised). Default: `"$CARGO_INSTALL_ROOT"`, then `"$CARGO_HOME"`,
     /// then `"$HOME/.cargo"`
     pub cargo_dir: (PathBuf, PathBuf),
@@ -57,8 +75,78 @@ pub struct Options {
     pub cargo_install_args: Vec<OsString>,
     /// The cargo to run for installations. Default: `None` (use "cargo")
     pub install_cargo: Option<OsString>,
+    /// The cargo-binstall to try first for eligible packages, or `None` to always use `cargo install`.
+    /// Default: `"$CARGO_UPDATE_BINSTALL"`, then `"cargo-binstall"`
+    pub binstall: Option<OsString>,
     /// Limit of concurrent jobs. Default: `None`
     pub jobs: Option<OsString>,
+    /// Target directory shared by all installs, made absolute. Default: `"$CARGO_TARGET_DIR"`, if set
+    pub target_dir: Option<PathBuf>,
+    /// How many packages to install at once. Default: `1`
+    pub jobs_packages: usize,
+    /// How many times to retry registry fetches failing with a network error, HTTP 429, or HTTP 5xx.
+    /// Default: `net.retry` from cargo config, then `2`
+    pub retries: Option<usize>,
+    /// How many seconds each registry or git connection may take. Default: `http.timeout` from cargo config, then none
+    pub network_timeout: Option<u64>,
+    /// How many seconds fetching the registries may take altogether, failing them after that. Default: `None`
+    pub deadline: Option<u64>,
+    /// Always update packages from the registry they were installed from, ignoring source replacement. Default: `false`
+    pub prefer_installed_registry: bool,
+    /// Remove cargo-update's cache of sparse registry responses before doing anything else. Default: `false`
+    pub clean_index_cache: bool,
+    /// List the git clones of packages that aren't installed anymore, and whether to remove them (with `-y`). Default: `None`
+    pub gc: Option<bool>,
+    /// List the executables installed for each package, and whether they're in the cargo bin directory, then exit. Default: `false`
+    pub list_executables: bool,
+    /// Re-fetch the sparse registry data, ignoring all caches. Default: `false`
+    pub fresh_index: bool,
+    /// Shell command to run once before updating anything, aborting if it fails. Default: `None`
+    pub pre_command: Option<OsString>,
+    /// Shell command to run once after updating everything. Default: `None`
+    pub post_command: Option<OsString>,
+    /// Consider versions requiring a newer Rust than the active `rustc`. Default: `false`
+    pub ignore_rust_version: bool,
+    /// Only check whether updating this package to this version would be an update. Default: `None`
+    pub compare_only: Option<(String, Semver)>,
+    /// Reinstall packages installed from a local directory when updating. Default: `false`
+    pub path_reinstall: bool,
+    /// Print the install commands instead of running them. Default: `false`
+    pub dry_run: bool,
+    /// Wait for other cargo-update runs updating the same cargo directory to finish, instead of failing. Default: `false`
+    pub wait: bool,
+    /// File to write a JSON summary of the update to. Default: `None`
+    pub summary_json: Option<PathBuf>,
+    /// Ask before updating, if stdin is a terminal. Default: `false`
+    pub confirm: bool,
+    /// Don't access the network, using only already-fetched registry data. Default: `false`
+    pub offline: bool,
+    /// Toolchain to install packages without a configured one with. Default: the channel from the closest `rust-toolchain` file,
+    /// unless `$RUSTUP_TOOLCHAIN` is set; `None` with `--no-rust-toolchain`
+    pub rust_toolchain: Option<String>,
+    /// Whether to colour the output. Default: if stdout is a terminal and `$NO_COLOR` is unset or empty
+    pub color: bool,
+    /// Authenticate to sparse registries with credentials from `.netrc`. Default: `true`
+    pub netrc: bool,
+    /// Clone git packages cargo hasn't with only their latest commit. Default: `false`
+    pub git_shallow: bool,
+    /// Retry libgit2 fetches of SSH URLs failing to connect or authenticate with the git CLI. Default: `true`
+    pub git_cli_fallback: bool,
+    /// Update cargo-update itself, after all other registry packages. Default: `false`
+    pub self_update: bool,
+    /// Skip packages whose registry can't be resolved, failing at the end, instead of stopping. Default: `false`
+    pub keep_going: bool,
+    /// Configuration applied on top of that of the packages installed this run, but not saved. Default: empty
+    pub run_config: Vec<ConfigOperation>,
+    /// Index to check crates.io packages against instead of the default, `sparse+`-prefixed if sparse. Default: `None`
+    pub crates_io_index: Option<String>,
+    /// File to record the installed packages' exact versions to before doing anything else. Default: `None`
+    pub snapshot: Option<PathBuf>,
+    /// Packages to install or update to their recorded versions; the registry ones are also in `to_update`. Default: empty
+    pub from_snapshot: BTreeMap<String, SnapshotPackage>,
+    /// Packages whose embedded `Cargo.lock` to enforce this run, those declared with `locked = true` in the `--from-manifest` one.
+    /// Default: empty
+    pub locked_packages: BTreeSet<String>,
 }
 
 /// Representation of the config application's all configurable values.
@@ -66,8 +154,8 @@ pub struct Options {
 pub struct ConfigOptions {
     /// The `cargo` home directory. Default: `"$CARGO_INSTALL_ROOT"`, then `"$CARGO_HOME"`, then `"$HOME/.cargo"`
     pub cargo_dir: PathBuf,
-    /// Crate to modify config for
-    pub package: String,
+    /// Crates to modify config for, with `DEFAULT_CONFIG_SECTION` standing for the defaults
+    pub packages: Vec<String>,
     /// What to do to the config, or display with empty
     pub ops: Vec<ConfigOperation>,
 }
@@ -93,16 +181,87 @@ impl Options {
                         Arg::from_usage("-a --all 'Update all packages'"),
                         Arg::from_usage("-l --list 'Don't update packages, only list and check if they need an update (all packages by default)'"),
                         Arg::from_usage("-f --force 'Update all packages regardless if they need updating'"),
+                        Arg::from_usage("--force-rebuild 'Also reinstall packages that don't need updating, within their version limits'"),
                         Arg::from_usage("-d --downdate 'Downdate packages to match latest unyanked registry version'"),
                         Arg::from_usage("-i --allow-no-update 'Allow for fresh-installing packages'"),
                         Arg::from_usage("-g --git 'Also update git packages'"),
-                        Arg::from_usage("-q --quiet 'No output printed to stdout'"),
+                        Arg::from_usage("--self 'Update cargo-update, after all other registry packages'"),
+                        Arg::from_usage("--git-shallow 'Clone git packages with only their latest commit to check for updates'"),
+                        Arg::from_usage("--no-git-cli-fallback 'Don't retry failed SSH fetches with the git CLI'"),
+                        Arg::from_usage("-q --quiet... 'No output printed to stdout; twice to hide warnings and failure summaries, too'"),
+                        Arg::from_usage("-v --verbose 'Show why each package does or doesn't need updating'"),
+                        Arg::from_usage("--keep-going 'Skip packages with registry errors instead of stopping, failing at the end'"),
                         Arg::from_usage("--locked 'Enforce packages' embedded Cargo.lock'"),
+                        Arg::from_usage("--with-feature=[FEATURE]... 'Also enable FEATURE for the packages installed this run'").number_of_values(1),
+                        Arg::from_usage("--without-default-features 'Disable default features for the packages installed this run'"),
+                        Arg::from_usage("--prefer-installed-registry 'Always update packages from the registry they were installed from'"),
+                        Arg::from_usage("--ignore-rust-version 'Don't skip versions requiring a newer Rust'"),
+                        Arg::from_usage("--path-reinstall 'Also reinstall packages installed from a local directory'"),
+                        Arg::from_usage("--dry-run 'Print the cargo install commands instead of running them'"),
+                        Arg::from_usage("--wait 'Wait for other cargo-update runs on the same cargo directory to finish, instead of failing'"),
+                        Arg::from_usage("--confirm 'Ask before updating'"),
+                        Arg::from_usage("--offline 'Use only already-fetched registry data, and pass --offline to cargo'")
+                            .conflicts_with("fresh-index"),
+                        Arg::from_usage("-y --yes 'Don't ask before updating (overrides --confirm)'"),
+                        Arg::from_usage("--color=[WHEN] 'Colour the output: auto, always, or never'")
+                            .possible_values(["auto", "always", "never"]),
+                        Arg::from_usage("--no-rust-toolchain 'Don't use the channel from rust-toolchain files as the default toolchain'"),
+                        Arg::from_usage("--no-netrc 'Don't take sparse registry credentials from .netrc'"),
+                        Arg::from_usage("--compare-only=[NAME VERSION] 'Only exit 0 if VERSION would be an update for NAME, 1 otherwise'")
+                            .number_of_values(2)
+                            .value_names(&["NAME", "VERSION"])
+                            .conflicts_with_all(&["all", "list", "PACKAGE", "git"]),
                         Arg::from_usage("-s --filter=[PACKAGE_FILTER]... 'Specify a filter a package must match to be considered'")
                             .number_of_values(1)
                             .validator(|s| PackageFilterElement::parse(&s).map(|_| ())),
+                        Arg::from_usage("--prefix=[PREFIX]... 'Only consider packages whose names start with PREFIX'").number_of_values(1),
+                        Arg::from_usage("--exclude-registry=[REGISTRY]... 'Don't consider packages from REGISTRY (name or URL)'")
+                            .number_of_values(1)
+                            .conflicts_with("only-registry"),
+                        Arg::from_usage("--only-registry=[REGISTRY]... 'Only consider registry packages from REGISTRY (name or URL)'")
+                            .number_of_values(1)
+                            .conflicts_with("exclude-registry"),
+                        Arg::from_usage("--max-versions-behind=[N] 'Only consider packages more than N releases behind'")
+                            .validator(|s| s.parse::<usize>().map(|_| ()).map_err(|e| format!("--max-versions-behind {} invalid: {}", s, e))),
+                        Arg::from_usage("--from-manifest=[MANIFEST] 'Install and update the tools listed in MANIFEST'")
+                            .validator(|s| fs::metadata(s).map(|_| ()).map_err(|e| format!("Manifest \"{}\": {}", s, e))),
+                        Arg::from_usage("--snapshot=[FILE] 'Record the installed packages' exact versions to FILE'").allow_invalid_utf8(true),
+                        Arg::from_usage("--from-snapshot=[FILE] 'Install and update packages to the versions recorded in FILE'").allow_invalid_utf8(true),
+                        Arg::from_usage("--stdin-packages 'Also read newline-separated PACKAGEs from stdin'"),
+                        Arg::from_usage("--packages-from=[FILE]... 'Also read newline-separated PACKAGEs from FILE'")
+                            .number_of_values(1)
+                            .allow_invalid_utf8(true),
                         Arg::from_usage("-r --install-cargo=[EXECUTABLE] 'Specify an alternative cargo to run for installations'").allow_invalid_utf8(true),
+                        Arg::from_usage("--binstall=[EXECUTABLE] 'Specify an alternative cargo-binstall to try first'")
+                            .env("CARGO_UPDATE_BINSTALL")
+                            .allow_invalid_utf8(true),
+                        Arg::from_usage("--no-binstall 'Never use cargo-binstall, always cargo install'"),
                         Arg::from_usage("-j --jobs=[JOBS] 'Limit number of parallel jobs.'").allow_invalid_utf8(true),
+                        Arg::from_usage("--target-dir=[DIR] 'Build all packages in DIR to reuse dependencies (uses more disk). Default: $CARGO_TARGET_DIR'")
+                            .allow_invalid_utf8(true),
+                        Arg::from_usage("--clean-index-cache 'Remove the cached sparse registry responses'"),
+                        Arg::from_usage("--gc 'List git clones of packages no longer installed, removing them with -y'"),
+                        Arg::from_usage("--list-executables 'List the executables of each package (all packages by default), and whether they exist'"),
+                        Arg::from_usage("--fresh-index 'Re-fetch sparse registry data, ignoring the caches'"),
+                        Arg::from_usage("--pre-command=[COMMAND] 'Run COMMAND once before updating, aborting if it fails'").allow_invalid_utf8(true),
+                        Arg::from_usage("--post-command=[COMMAND] 'Run COMMAND once after updating'").allow_invalid_utf8(true),
+                        Arg::from_usage("--summary-json=[PATH] 'Write a JSON summary of the update to PATH'").allow_invalid_utf8(true),
+                        Arg::from_usage("--jobs-packages=[N] 'Install at most N packages at once'").validator(|s| match s.parse::<usize>() {
+                            Ok(0) => Err("--jobs-packages must be at least 1".to_string()),
+                            Ok(_) => Ok(()),
+                            Err(e) => Err(format!("--jobs-packages {} invalid: {}", s, e)),
+                        }),
+                        Arg::from_usage("--registry=[REGISTRY] 'Registry to install PACKAGEs without an explicit one from'").empty_values(false),
+                        Arg::from_usage("--crates-io-index=[URL] 'Check crates.io packages against the index at URL (sparse+URL for sparse)'")
+                            .validator(|s| {
+                                Url::parse(s.strip_prefix("sparse+").unwrap_or(s)).map(|_| ()).map_err(|e| format!("--crates-io-index {} invalid: {}", s, e))
+                            }),
+                        Arg::from_usage("--retries=[N] 'Retry registry fetches failing with transient errors N times'")
+                            .validator(|s| s.parse::<usize>().map(|_| ()).map_err(|e| format!("--retries {} invalid: {}", s, e))),
+                        Arg::from_usage("--network-timeout=[SECS] 'Fail each registry or git connection after SECS seconds'")
+                            .validator(|s| s.parse::<u64>().map(|_| ()).map_err(|e| format!("--network-timeout {} invalid: {}", s, e))),
+                        Arg::from_usage("--deadline=[SECS] 'Fail fetching the registries if it takes over SECS seconds altogether'")
+                            .validator(|s| s.parse::<u64>().map(|_| ()).map_err(|e| format!("--deadline {} invalid: {}", s, e))),
                         Arg::with_name("cargo_install_opts")
                             .long("__cargo_install_opts")
                             .env("CARGO_INSTALL_OPTS")
@@ -118,26 +277,65 @@ impl Options {
             .get_matches();
         let matches = matches.subcommand_matches("install-update").unwrap();
 
-        let all = matches.is_present("all");
+        let listed = ["PACKAGE", "stdin-packages", "packages-from", "from-manifest", "self", "from-snapshot"].iter().any(|a| matches.is_present(a));
+        let all = matches.is_present("all") || (matches.is_present("prefix") && !listed);
         let update = !matches.is_present("list");
+        let from_snapshot = matches.value_of_os("from-snapshot")
+            .map(|snapshot| SnapshotPackage::read(Path::new(snapshot)).unwrap_or_else(|e| clerror(format_args!("{}: {}", Path::new(snapshot).display(), e))))
+            .unwrap_or_default();
+        let manifest = matches.value_of("from-manifest")
+            .map(|manifest| (manifest, fs::read_to_string(manifest).unwrap_or_else(|e| clerror(format_args!("{}: {}", manifest, e)))));
         Options {
-            to_update: match (all || !update, matches.values_of("PACKAGE")) {
-                (_, Some(pkgs)) => {
-                    let packages: Vec<_> = pkgs.map(package_parse).map(Result::unwrap).collect();
-                    packages.unique_via(|l, r| l.0 == r.0)
+            to_update: {
+                let registry = matches.value_of("registry").unwrap_or(DEFAULT_REGISTRY);
+                let mut packages: Vec<_> =
+                    matches.values_of("PACKAGE").into_iter().flatten().map(|p| package_parse_in(p, registry)).map(Result::unwrap).collect();
+                if matches.is_present("stdin-packages") {
+                    packages.extend(read_package_list_in(stdin().lock(), registry).unwrap_or_else(|e| clerror(format_args!("stdin: {}", e))));
+                }
+                for file in matches.values_of_os("packages-from").into_iter().flatten() {
+                    packages.extend(File::open(file)
+                        .map_err(|e| e.to_string())
+                        .and_then(|f| read_package_list_in(BufReader::new(f), registry))
+                        .unwrap_or_else(|e| clerror(format_args!("{}: {}", Path::new(file).display(), e))));
+                }
+                if matches.is_present("self") {
+                    packages.push(("cargo-update".to_string(), None, DEFAULT_REGISTRY.to_string(), false));
                 }
-                (true, None) => vec![],
-                (false, None) => clerror(format_args!("Need at least one PACKAGE without --all")),
+                if let Some((path, ref manifest)) = manifest {
+                    packages.extend(manifest_packages(manifest).unwrap_or_else(|e| clerror(format_args!("{}: {}", path, e))));
+                }
+                packages.extend(from_snapshot.iter().flat_map(|(name, package)| {
+                    package.version.as_ref().map(|version| {
+                        (name.clone(),
+                         Some(SemverReq::parse(&format!("={}", version)).unwrap()),
+                         package.registry.clone().unwrap_or_else(|| DEFAULT_REGISTRY.to_string()),
+                         true)
+                    })
+                }));
+                if packages.is_empty() && update && !all && !matches.is_present("stdin-packages") && !matches.is_present("packages-from") &&
+                   !matches.is_present("from-manifest") && !matches.is_present("clean-index-cache") && !matches.is_present("gc") &&
+                   !matches.is_present("compare-only") && !matches.is_present("snapshot") && !matches.is_present("from-snapshot") &&
+                   !matches.is_present("list-executables") {
+                    clerror(format_args!("Need at least one PACKAGE without --all"));
+                }
+                packages.unique_via(|l, r| l.0 == r.0)
             },
             all: all,
             update: update,
-            install: matches.is_present("allow-no-update"),
+            install: matches.is_present("allow-no-update") || matches.is_present("from-manifest") || matches.is_present("from-snapshot"),
             force: matches.is_present("force"),
+            force_rebuild: matches.is_present("force-rebuild"),
             downdate: matches.is_present("downdate"),
-            update_git: matches.is_present("git"),
-            quiet: matches.is_present("quiet"),
+            update_git: matches.is_present("git") || from_snapshot.values().any(|p| p.rev.is_some()),
+            quiet: matches.occurrences_of("quiet"),
+            verbose: matches.is_present("verbose"),
             locked: matches.is_present("locked"),
             filter: matches.values_of("filter").map(|pfs| pfs.flat_map(PackageFilterElement::parse).collect()).unwrap_or_else(|| vec![]),
+            prefixes: matches.values_of("prefix").into_iter().flatten().map(str::to_string).collect(),
+            exclude_registries: matches.values_of("exclude-registry").into_iter().flatten().map(str::to_string).collect(),
+            only_registries: matches.values_of("only-registry").into_iter().flatten().map(str::to_string).collect(),
+            max_versions_behind: matches.value_of("max-versions-behind").map(|n| n.parse().unwrap()),
             cargo_dir: cargo_dir(matches.value_of_os("cargo-dir")),
             temp_dir: {
                 if let Some(tmpdir) = matches.value_of("temp-dir") {
@@ -148,7 +346,72 @@ impl Options {
             },
             cargo_install_args: matches.values_of_os("cargo_install_opts").into_iter().flat_map(|cio| cio.map(OsStr::to_os_string)).collect(),
             install_cargo: matches.value_of_os("install-cargo").map(OsStr::to_os_string),
+            binstall: if matches.is_present("no-binstall") {
+                None
+            } else {
+                Some(matches.value_of_os("binstall").filter(|b| !b.is_empty()).unwrap_or(OsStr::new("cargo-binstall")).to_os_string())
+            },
             jobs: matches.value_of_os("jobs").map(OsStr::to_os_string),
+            target_dir: matches.value_of_os("target-dir")
+                .map(OsStr::to_os_string)
+                .or_else(|| env::var_os("CARGO_TARGET_DIR").filter(|td| !td.is_empty()))
+                .map(|td| env::current_dir().map(|cd| cd.join(&td)).unwrap_or_else(|_| td.into())),
+            jobs_packages: matches.value_of("jobs-packages").map(|j| j.parse().unwrap()).unwrap_or(1),
+            retries: matches.value_of("retries").map(|r| r.parse().unwrap()),
+            network_timeout: matches.value_of("network-timeout").map(|t| t.parse().unwrap()),
+            deadline: matches.value_of("deadline").map(|d| d.parse().unwrap()),
+            prefer_installed_registry: matches.is_present("prefer-installed-registry"),
+            clean_index_cache: matches.is_present("clean-index-cache"),
+            gc: Some(matches.is_present("yes") && !matches.is_present("dry-run")).filter(|_| matches.is_present("gc")),
+            list_executables: matches.is_present("list-executables"),
+            fresh_index: matches.is_present("fresh-index"),
+            pre_command: matches.value_of_os("pre-command").map(OsStr::to_os_string),
+            post_command: matches.value_of_os("post-command").map(OsStr::to_os_string),
+            ignore_rust_version: matches.is_present("ignore-rust-version"),
+            path_reinstall: matches.is_present("path-reinstall"),
+            dry_run: matches.is_present("dry-run"),
+            wait: matches.is_present("wait"),
+            summary_json: matches.value_of_os("summary-json").map(PathBuf::from),
+            confirm: matches.is_present("confirm") && !matches.is_present("yes"),
+            offline: matches.is_present("offline"),
+            rust_toolchain: if matches.is_present("no-rust-toolchain") || env::var_os("RUSTUP_TOOLCHAIN").is_some() {
+                None
+            } else {
+                env::current_dir().ok().and_then(|cd| find_rust_toolchain(&cd))
+            },
+            color: match matches.value_of("color").unwrap_or("auto") {
+                "always" => true,
+                "never" => false,
+                _ => stdout().is_terminal() && env::var_os("NO_COLOR").map(|nc| nc.is_empty()).unwrap_or(true),
+            },
+            netrc: !matches.is_present("no-netrc"),
+            git_shallow: matches.is_present("git-shallow"),
+            git_cli_fallback: !matches.is_present("no-git-cli-fallback"),
+            self_update: matches.is_present("self"),
+            keep_going: matches.is_present("keep-going"),
+            crates_io_index: matches.value_of("crates-io-index").map(str::to_string),
+            snapshot: matches.value_of_os("snapshot").map(PathBuf::from),
+            run_config: matches.values_of("with-feature")
+                .into_iter()
+                .flatten()
+                .map(str::to_string)
+                .map(ConfigOperation::AddFeature)
+                .chain(if matches.is_present("without-default-features") {
+                    Some(ConfigOperation::DefaultFeatures(false))
+                } else {
+                    None
+                })
+                .collect(),
+            compare_only: matches.values_of("compare-only").map(|mut nv| {
+                let name = nv.next().unwrap().to_string();
+                let version = nv.next().unwrap();
+                (name,
+                 Semver::parse(version).unwrap_or_else(|e| clerror(format_args!("Version {} provided for --compare-only invalid: {}", version, e))))
+            }),
+            from_snapshot,
+            locked_packages: manifest.as_ref()
+                .map(|(path, manifest)| manifest_locked_packages(manifest).unwrap_or_else(|e| clerror(format_args!("{}: {}", path, e))))
+                .unwrap_or_default(),
         }
     }
 }
@@ -172,13 +435,32 @@ impl ConfigOptions {
                         Arg::from_usage("-d --default-features=[DEFAULT_FEATURES] 'Whether to allow default features'")
                             .possible_values(&["1", "yes", "true", "0", "no", "false"])
                             .hide_possible_values(true),
+                        Arg::from_usage("--all-features 'Compile the package with all features'").conflicts_with("no-all-features").conflicts_with("feature"),
+                        Arg::from_usage("--no-all-features 'Compile the package with the configured features'").conflicts_with("all-features"),
                         Arg::from_usage("--debug 'Compile the package in debug (\"dev\") mode'").conflicts_with("release").conflicts_with("build-profile"),
-                        Arg::from_usage("--release 'Compile the package in release mode'").conflicts_with("debug").conflicts_with("build-profile"),
-                        Arg::from_usage("--build-profile=[PROFILE] 'Compile the package in the given profile'").conflicts_with("debug").conflicts_with("release"),
+                        Arg::from_usage("--release 'Compile the package in release mode'")
+                            .visible_alias("no-profile")
+                            .conflicts_with("debug")
+                            .conflicts_with("build-profile"),
+                        Arg::from_usage("--build-profile=[PROFILE] 'Compile the package in the given profile'")
+                            .visible_alias("profile")
+                            .conflicts_with("debug")
+                            .conflicts_with("release"),
+                        Arg::from_usage("--target=[TRIPLE] 'Compile the package for the given target triple'").conflicts_with("no-target"),
+                        Arg::from_usage("--no-target 'Compile the package for the host'").conflicts_with("target"),
+                        Arg::from_usage("--install-arg=[ARG]... 'Pass ARG to cargo install'").number_of_values(1).allow_hyphen_values(true),
+                        Arg::from_usage("--clear-install-args 'Don't pass additional arguments to cargo install'"),
+                        Arg::from_usage("--git-tag=[TAG] 'Pin the git package to TAG'").conflicts_with("git-rev").conflicts_with("no-git-ref"),
+                        Arg::from_usage("--git-rev=[REV] 'Pin the git package to commit REV'").conflicts_with("no-git-ref"),
+                        Arg::from_usage("--no-git-ref 'Track the git package's branch'"),
                         Arg::from_usage("--install-prereleases 'Install prerelease versions'").conflicts_with("no-install-prereleases"),
                         Arg::from_usage("--no-install-prereleases 'Filter out prerelease versions'").conflicts_with("install-prereleases"),
                         Arg::from_usage("--enforce-lock 'Require Cargo.lock to be up to date'").conflicts_with("no-enforce-lock"),
                         Arg::from_usage("--no-enforce-lock 'Don't enforce Cargo.lock'").conflicts_with("enforce-lock"),
+                        Arg::from_usage("--frozen 'Require Cargo.lock to be up to date and don't access the network'").conflicts_with("no-frozen"),
+                        Arg::from_usage("--no-frozen 'Don't pass --frozen'").conflicts_with("frozen"),
+                        Arg::from_usage("--offline 'Don't access the network'").conflicts_with("no-offline"),
+                        Arg::from_usage("--no-offline 'Don't pass --offline'").conflicts_with("offline"),
                         Arg::from_usage("--respect-binaries 'Only install already installed binaries'").conflicts_with("no-respect-binaries"),
                         Arg::from_usage("--no-respect-binaries 'Install all binaries'").conflicts_with("respect-binaries"),
                         Arg::from_usage("-v --version=[VERSION_REQ] 'Require a cargo-compatible version range'")
@@ -206,14 +488,49 @@ impl ConfigOptions {
                             } else {
                                 Ok(())
                             }),
+                        Arg::from_usage("--post-install=[COMMAND] 'Run COMMAND after installing the package'").conflicts_with("no-post-install"),
+                        Arg::from_usage("--no-post-install 'Don't run anything after installing the package'"),
+                        Arg::from_usage("--jobs=[JOBS] 'Build the package with JOBS parallel jobs'")
+                            .validator(|s| match s.parse::<u32>() {
+                                Ok(0) => Err("--jobs must be at least 1".to_string()),
+                                Ok(_) => Ok(()),
+                                Err(e) => Err(format!("--jobs {} invalid: {}", s, e)),
+                            })
+                            .conflicts_with("no-jobs"),
+                        Arg::from_usage("--no-jobs 'Build the package with the default number of parallel jobs'"),
+                        Arg::from_usage("--rustflag=[FLAG]... 'Build the package with FLAG in RUSTFLAGS'").number_of_values(1).allow_hyphen_values(true),
+                        Arg::from_usage("--clear-rustflags 'Don't set RUSTFLAGS'"),
+                        Arg::from_usage("--append-rustflags 'Append the flags to the inherited RUSTFLAGS'").conflicts_with("no-append-rustflags"),
+                        Arg::from_usage("--no-append-rustflags 'Replace the inherited RUSTFLAGS'").conflicts_with("append-rustflags"),
+                        Arg::from_usage("--install-cargo=[PATH] 'Install the package with the cargo executable at PATH'").conflicts_with("no-install-cargo"),
+                        Arg::from_usage("--no-install-cargo 'Install the package with the default cargo'"),
+                        Arg::from_usage("--hold 'Only update the package if it's named explicitly'").conflicts_with("no-hold"),
+                        Arg::from_usage("--no-hold 'Update the package with --all, too'").conflicts_with("hold"),
                         Arg::from_usage("-r --reset 'Roll back the configuration to the defaults.'"),
-                        Arg::from_usage("<PACKAGE> 'Package to configure'").empty_values(false)]))
+                        Arg::from_usage("-p --packages=[PACKAGES]... 'Also configure these comma-separated packages'")
+                            .use_delimiter(true)
+                            .require_delimiter(true)
+                            .empty_values(false),
+                        Arg::from_usage("--default 'Also configure the defaults every package is based on'"),
+                        Arg::from_usage("[PACKAGE] 'Package to configure'").empty_values(false).required_unless_one(["packages", "default"])]))
             .get_matches();
         let matches = matches.subcommand_matches("install-update-config").unwrap();
 
         ConfigOptions {
             cargo_dir: cargo_dir(matches.value_of_os("cargo-dir")).1,
-            package: matches.value_of("PACKAGE").unwrap().to_string(),
+            packages: {
+                let packages: Vec<_> = matches.value_of("PACKAGE")
+                    .into_iter()
+                    .chain(matches.values_of("packages").into_iter().flatten())
+                    .chain(if matches.is_present("default") {
+                        Some(DEFAULT_CONFIG_SECTION)
+                    } else {
+                        None
+                    })
+                    .map(str::to_string)
+                    .collect();
+                packages.unique()
+            },
             ops: matches.value_of("toolchain")
                 .map(|t| if t.is_empty() {
                     ConfigOperation::RemoveToolchain
@@ -224,12 +541,29 @@ impl ConfigOptions {
                 .chain(matches.values_of("feature").into_iter().flatten().map(str::to_string).map(ConfigOperation::AddFeature))
                 .chain(matches.values_of("no-feature").into_iter().flatten().map(str::to_string).map(ConfigOperation::RemoveFeature))
                 .chain(matches.value_of("default-features").map(|d| ["1", "yes", "true"].contains(&d)).map(ConfigOperation::DefaultFeatures).into_iter())
+                .chain(match (matches.is_present("all-features"), matches.is_present("no-all-features")) {
+                    (true, _) => Some(ConfigOperation::SetAllFeatures(true)),
+                    (_, true) => Some(ConfigOperation::SetAllFeatures(false)),
+                    _ => None,
+                })
                 .chain(match (matches.is_present("debug"), matches.is_present("release"), matches.value_of("build-profile")) {
                     (true, _, _) => Some(ConfigOperation::SetBuildProfile("dev".into())),
                     (_, true, _) => Some(ConfigOperation::SetBuildProfile("release".into())),
                     (_, _, Some(prof)) => Some(ConfigOperation::SetBuildProfile(prof.to_string().into())),
                     _ => None,
                 })
+                .chain(match (matches.value_of("target"), matches.is_present("no-target")) {
+                    (Some(tt), _) => Some(ConfigOperation::SetTargetTriple(tt.to_string())),
+                    (_, true) => Some(ConfigOperation::RemoveTargetTriple),
+                    _ => None,
+                })
+                .chain(matches.index_of("clear-install-args").map(|_| ConfigOperation::ClearInstallArgs))
+                .chain(matches.values_of("install-arg").into_iter().flatten().map(str::to_string).map(ConfigOperation::AddInstallArg))
+                .chain(match (matches.value_of("git-tag").or_else(|| matches.value_of("git-rev")), matches.is_present("no-git-ref")) {
+                    (Some(gr), _) => Some(ConfigOperation::SetGitRef(gr.to_string())),
+                    (_, true) => Some(ConfigOperation::RemoveGitRef),
+                    _ => None,
+                })
                 .chain(match (matches.is_present("install-prereleases"), matches.is_present("no-install-prereleases")) {
                     (true, _) => Some(ConfigOperation::SetInstallPrereleases(true)),
                     (_, true) => Some(ConfigOperation::SetInstallPrereleases(false)),
@@ -240,6 +574,16 @@ impl ConfigOptions {
                     (_, true) => Some(ConfigOperation::SetEnforceLock(false)),
                     _ => None,
                 })
+                .chain(match (matches.is_present("frozen"), matches.is_present("no-frozen")) {
+                    (true, _) => Some(ConfigOperation::SetFrozen(true)),
+                    (_, true) => Some(ConfigOperation::SetFrozen(false)),
+                    _ => None,
+                })
+                .chain(match (matches.is_present("offline"), matches.is_present("no-offline")) {
+                    (true, _) => Some(ConfigOperation::SetOffline(true)),
+                    (_, true) => Some(ConfigOperation::SetOffline(false)),
+                    _ => None,
+                })
                 .chain(match (matches.is_present("respect-binaries"), matches.is_present("no-respect-binaries")) {
                     (true, _) => Some(ConfigOperation::SetRespectBinaries(true)),
                     (_, true) => Some(ConfigOperation::SetRespectBinaries(false)),
@@ -257,6 +601,33 @@ impl ConfigOptions {
                     .map(|(k, v)| ConfigOperation::SetEnvironment(k.to_string(), v.to_string())))
                 .chain(matches.values_of("clear-environment").into_iter().flatten().map(str::to_string).map(ConfigOperation::ClearEnvironment))
                 .chain(matches.values_of("inherit-environment").into_iter().flatten().map(str::to_string).map(ConfigOperation::InheritEnvironment))
+                .chain(match (matches.value_of("post-install"), matches.is_present("no-post-install")) {
+                    (Some(cmd), _) => Some(ConfigOperation::SetPostInstall(cmd.to_string())),
+                    (_, true) => Some(ConfigOperation::ClearPostInstall),
+                    _ => None,
+                })
+                .chain(match (matches.value_of("jobs"), matches.is_present("no-jobs")) {
+                    (Some(j), _) => Some(ConfigOperation::SetJobs(j.parse().unwrap())),
+                    (_, true) => Some(ConfigOperation::RemoveJobs),
+                    _ => None,
+                })
+                .chain(matches.index_of("clear-rustflags").map(|_| ConfigOperation::ClearRustflags))
+                .chain(matches.values_of("rustflag").into_iter().flatten().map(str::to_string).map(ConfigOperation::AddRustflag))
+                .chain(match (matches.is_present("append-rustflags"), matches.is_present("no-append-rustflags")) {
+                    (true, _) => Some(ConfigOperation::SetAppendRustflags(true)),
+                    (_, true) => Some(ConfigOperation::SetAppendRustflags(false)),
+                    _ => None,
+                })
+                .chain(match (matches.value_of("install-cargo"), matches.is_present("no-install-cargo")) {
+                    (Some(ic), _) => Some(ConfigOperation::SetInstallCargo(ic.to_string())),
+                    (_, true) => Some(ConfigOperation::RemoveInstallCargo),
+                    _ => None,
+                })
+                .chain(match (matches.is_present("hold"), matches.is_present("no-hold")) {
+                    (true, _) => Some(ConfigOperation::SetHold(true)),
+                    (_, true) => Some(ConfigOperation::SetHold(false)),
+                    _ => None,
+                })
                 .chain(matches.index_of("reset").map(|_| ConfigOperation::ResetConfig))
                 .collect(),
         }
@@ -287,7 +658,58 @@ fn existing_dir_validator(label: &str, s: &str) -> Result<(), String> {
     fs::canonicalize(s).map(|_| ()).map_err(|_| format!("{} directory \"{}\" not found", label, s))
 }
 
-fn package_parse(s: &str) -> Result<(String, Option<Semver>, String), String> {
+/// The registry packages are installed from by default.
+const DEFAULT_REGISTRY: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// Parse a package specification, as accepted in the `PACKAGE` positional argument.
+///
+/// The specification is `[(REGISTRY_URL):]NAME[:VERSION_REQ]`, with the registry defaulting to crates.io;
+/// `NAME@VERSION_REQ`, as in `cargo install`, is accepted too.
+///
+/// `VERSION_REQ` is parsed by `ops::parse_version_spec()`: the newest version satisfying a requirement like `^1.2` is updated to,
+/// a bare `VERSION` is the maximum version to update to (`<=VERSION`), and `=VERSION` is the exact version to install,
+/// even if older than the installed one, with `--downdate` or `--force`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate cargo_update;
+/// # extern crate semver;
+/// # use cargo_update::package_parse;
+/// # use semver::VersionReq as SemverReq;
+/// # fn main() {
+/// assert_eq!(package_parse("cargo-update:1.2.3"),
+///            Ok(("cargo-update".to_string(), Some(SemverReq::parse("<=1.2.3").unwrap()),
+///                "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+/// assert_eq!(package_parse("cargo-update:^1.2"),
+///            Ok(("cargo-update".to_string(), Some(SemverReq::parse("^1.2").unwrap()),
+///                "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+/// assert_eq!(package_parse("cargo-update:=1.2.3"),
+///            Ok(("cargo-update".to_string(), Some(SemverReq::parse("=1.2.3").unwrap()),
+///                "https://github.com/rust-lang/crates.io-index".to_string(), true)));
+/// assert_eq!(package_parse("cargo-update@^1.2"),
+///            Ok(("cargo-update".to_string(), Some(SemverReq::parse("^1.2").unwrap()),
+///                "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+/// assert_eq!(package_parse("(file:///usr/local/share/cargo):racer"),
+///            Ok(("racer".to_string(), None, "file:///usr/local/share/cargo".to_string(), false)));
+/// # }
+/// ```
+pub fn package_parse(s: &str) -> Result<PackageSpec, String> {
+    package_parse_in(s, DEFAULT_REGISTRY)
+}
+
+/// Parse a package specification like `package_parse()`, but with the registry defaulting to the specified one.
+///
+/// # Examples
+///
+/// ```
+/// # use cargo_update::package_parse_in;
+/// assert_eq!(package_parse_in("cargo-update", "my-registry"),
+///            Ok(("cargo-update".to_string(), None, "my-registry".to_string(), false)));
+/// assert_eq!(package_parse_in("(file:///usr/local/share/cargo):racer", "my-registry"),
+///            Ok(("racer".to_string(), None, "file:///usr/local/share/cargo".to_string(), false)));
+/// ```
+pub fn package_parse_in(s: &str, default_registry: &str) -> Result<PackageSpec, String> {
     let mut registry_url = None;
     let mut s = &s[..];
     if s.starts_with('(') {
@@ -297,15 +719,56 @@ fn package_parse(s: &str) -> Result<(String, Option<Semver>, String), String> {
         }
     }
 
-    let registry_url = registry_url.unwrap_or_else(|| "https://github.com/rust-lang/crates.io-index".to_string());
+    let registry_url = registry_url.unwrap_or_else(|| default_registry.to_string());
 
-    if let Some(idx) = s.find(':') {
-        Ok((s[0..idx].to_string(),
-            Some(Semver::parse(&s[idx + 1..]).map_err(|e| format!("Version {} provided for package {} invalid: {}", &s[idx + 1..], &s[0..idx], e))?),
-            registry_url))
+    if let Some(idx) = s.find([':', '@']) {
+        let version = &s[idx + 1..];
+        let (req, exact) = parse_version_spec(version)
+            .map_err(|e| format!("Version {} provided for package {} invalid: {}", version.strip_prefix('=').unwrap_or(version), &s[0..idx], e))?;
+        Ok((s[0..idx].to_string(), Some(req), registry_url, exact))
     } else {
-        Ok((s.to_string(), None, registry_url))
+        Ok((s.to_string(), None, registry_url, false))
+    }
+}
+
+/// Read newline-separated package specifications, as accepted by `package_parse()`.
+///
+/// Surrounding whitespace is trimmed and empty lines and `#` comment lines are skipped, so empty input yields no packages.
+///
+/// # Examples
+///
+/// ```
+/// # use cargo_update::read_package_list;
+/// let packages = read_package_list(&b"# Team tools\ncargo-update\n\n  racer:1.2.10\n"[..]).unwrap();
+/// assert_eq!(packages.len(), 2);
+/// assert_eq!(packages[0].0, "cargo-update");
+/// assert_eq!(packages[1].0, "racer");
+/// ```
+pub fn read_package_list<R: BufRead>(r: R) -> Result<Vec<PackageSpec>, String> {
+    read_package_list_in(r, DEFAULT_REGISTRY)
+}
+
+/// Read newline-separated package specifications like `read_package_list()`, but with the registry defaulting to the specified one,
+/// as in `package_parse_in()`.
+///
+/// # Examples
+///
+/// ```
+/// # use cargo_update::read_package_list_in;
+/// let packages = read_package_list_in(&b"cargo-update\n(file:///usr/local/share/cargo):racer\n"[..], "my-registry").unwrap();
+/// assert_eq!(packages[0].2, "my-registry");
+/// assert_eq!(packages[1].2, "file:///usr/local/share/cargo");
+/// ```
+pub fn read_package_list_in<R: BufRead>(r: R, default_registry: &str) -> Result<Vec<PackageSpec>, String> {
+    let mut ret = vec![];
+    for (i, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            ret.push(package_parse_in(line, default_registry).map_err(|e| format!("line {}: {}", i + 1, e))?);
+        }
     }
+    Ok(ret)
 }
 
 
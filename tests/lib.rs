@@ -1,4 +1,8 @@
 extern crate cargo_update;
 extern crate semver;
+extern crate git2;
+#[cfg(feature = "logging")]
+extern crate log;
 
+mod options;
 mod ops;
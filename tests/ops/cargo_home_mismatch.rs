@@ -0,0 +1,37 @@
+use cargo_update::ops::cargo_home_mismatch;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn divergent() {
+    let td = temp_dir().join("cargo_update-test").join("cargo_home_mismatch-divergent");
+    let _ = fs::create_dir_all(td.join("cargo-dir"));
+    let _ = fs::create_dir_all(td.join("cargo-home"));
+    let cargo_dir = fs::canonicalize(td.join("cargo-dir")).unwrap();
+    let cargo_home = fs::canonicalize(td.join("cargo-home")).unwrap();
+
+    assert_eq!(cargo_home_mismatch(&cargo_dir, Some(td.join("cargo-home").as_os_str()), None), Some(cargo_home.clone()));
+    assert_eq!(cargo_home_mismatch(&cargo_dir, Some(td.join("cargo-home").as_os_str()), Some(td.join("cargo-home").as_os_str())),
+               Some(cargo_home));
+}
+
+#[test]
+fn same() {
+    let td = temp_dir().join("cargo_update-test").join("cargo_home_mismatch-same");
+    let _ = fs::create_dir_all(td.join("cargo-dir"));
+    let cargo_dir = fs::canonicalize(td.join("cargo-dir")).unwrap();
+
+    assert_eq!(cargo_home_mismatch(&cargo_dir, Some(td.join("cargo-dir").join("..").join("cargo-dir").as_os_str()), None), None);
+    assert_eq!(cargo_home_mismatch(&cargo_dir, None, None), None);
+}
+
+#[test]
+fn install_root() {
+    let td = temp_dir().join("cargo_update-test").join("cargo_home_mismatch-install_root");
+    let _ = fs::create_dir_all(td.join("cargo-dir"));
+    let _ = fs::create_dir_all(td.join("cargo-home"));
+    let cargo_dir = fs::canonicalize(td.join("cargo-dir")).unwrap();
+
+    assert_eq!(cargo_home_mismatch(&cargo_dir, Some(td.join("cargo-home").as_os_str()), Some(td.join("cargo-dir").as_os_str())), None);
+}
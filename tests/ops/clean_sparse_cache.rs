@@ -0,0 +1,26 @@
+use cargo_update::ops::{SparseIndexCache, clean_sparse_cache, sparse_cache_dir};
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn existent() {
+    let td = temp_dir().join("cargo_update-test").join("clean_sparse_cache-existent");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(td.join("registry").join("index")).unwrap();
+
+    SparseIndexCache::parse(b"", "https://index.crates.io/").write(&SparseIndexCache::path(&td, "https://index.crates.io/")).unwrap();
+    assert!(SparseIndexCache::path(&td, "https://index.crates.io/").starts_with(sparse_cache_dir(&td)));
+
+    assert_eq!(clean_sparse_cache(&td), Ok(()));
+    assert!(!sparse_cache_dir(&td).exists());
+    assert!(td.join("registry").join("index").exists());
+}
+
+#[test]
+fn non_existent() {
+    let td = temp_dir().join("cargo_update-test").join("clean_sparse_cache-nonexistent");
+    let _ = fs::remove_dir_all(&td);
+
+    assert_eq!(clean_sparse_cache(&td), Ok(()));
+}
@@ -0,0 +1,25 @@
+use cargo_update::ops::command_line;
+use std::process::Command;
+
+
+#[test]
+fn plain() {
+    let mut cmd = Command::new("cargo");
+    cmd.args(&["install", "--root", "/home/user/.cargo", "--version", "=1.2.3", "--registry", "crates-io", "cargo-update"]);
+    assert_eq!(command_line(&cmd), "cargo install --root /home/user/.cargo --version =1.2.3 --registry crates-io cargo-update");
+}
+
+#[test]
+fn quoted() {
+    let mut cmd = Command::new("cargo");
+    cmd.args(&["install", "--features", "a b", "", "it's", "$HOME"]);
+    assert_eq!(command_line(&cmd), r#"cargo install --features 'a b' '' 'it'\''s' '$HOME'"#);
+}
+
+#[test]
+fn environment() {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("install").env("RUSTC_WRAPPER", "sccache").env("RUSTFLAGS", "-C target-cpu=native").env_remove("CARGO_TARGET_DIR");
+    assert_eq!(command_line(&cmd),
+               "env -u CARGO_TARGET_DIR RUSTC_WRAPPER=sccache RUSTFLAGS='-C target-cpu=native' cargo install");
+}
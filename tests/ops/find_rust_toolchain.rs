@@ -0,0 +1,51 @@
+use cargo_update::ops::find_rust_toolchain;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn toml() {
+    let td = temp_dir().join("cargo_update-test").join("find_rust_toolchain-toml");
+    let _ = fs::create_dir_all(td.join("src").join("bin"));
+    fs::write(td.join("rust-toolchain.toml"), "[toolchain]\nchannel = \"nightly-2024-01-01\"\ncomponents = [\"rustfmt\"]\n").unwrap();
+
+    assert_eq!(find_rust_toolchain(&td), Some("nightly-2024-01-01".to_string()));
+    assert_eq!(find_rust_toolchain(&td.join("src").join("bin")), Some("nightly-2024-01-01".to_string()));
+}
+
+#[test]
+fn legacy() {
+    let td = temp_dir().join("cargo_update-test").join("find_rust_toolchain-legacy");
+    let _ = fs::create_dir_all(&td);
+    fs::write(td.join("rust-toolchain"), "stable\n").unwrap();
+
+    assert_eq!(find_rust_toolchain(&td), Some("stable".to_string()));
+}
+
+#[test]
+fn legacy_toml() {
+    let td = temp_dir().join("cargo_update-test").join("find_rust_toolchain-legacy_toml");
+    let _ = fs::create_dir_all(&td);
+    fs::write(td.join("rust-toolchain"), "[toolchain]\nchannel = \"1.71.1\"\n").unwrap();
+
+    assert_eq!(find_rust_toolchain(&td), Some("1.71.1".to_string()));
+}
+
+#[test]
+fn closest() {
+    let td = temp_dir().join("cargo_update-test").join("find_rust_toolchain-closest");
+    let _ = fs::create_dir_all(td.join("inner"));
+    fs::write(td.join("rust-toolchain"), "stable\n").unwrap();
+    fs::write(td.join("inner").join("rust-toolchain.toml"), "[toolchain]\nchannel = \"beta\"\n").unwrap();
+
+    assert_eq!(find_rust_toolchain(&td.join("inner")), Some("beta".to_string()));
+}
+
+#[test]
+fn path() {
+    let td = temp_dir().join("cargo_update-test").join("find_rust_toolchain-path");
+    let _ = fs::create_dir_all(&td);
+    fs::write(td.join("rust-toolchain.toml"), "[toolchain]\npath = \"/opt/rust\"\n").unwrap();
+
+    assert_eq!(find_rust_toolchain(&td), None);
+}
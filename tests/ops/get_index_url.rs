@@ -1,10 +1,11 @@
-use cargo_update::ops::get_index_url;
-use std::path::PathBuf;
+use cargo_update::ops::{RegistrySources, Error, get_recorded_index_url, get_index_url};
+use std::path::{PathBuf, Path};
 use std::env::temp_dir;
 use std::fs;
 
 
 static TEST_DATA: &[u8] = include_bytes!("../../test-data/cargo.config");
+static MIRROR_TEST_DATA: &[u8] = include_bytes!("../../test-data/cargo-mirror.config");
 
 
 #[test]
@@ -13,9 +14,9 @@ fn default_vs_sparse() {
         let crates_file = prep_config("default_vs_sparse", suffix);
         fs::remove_file(crates_file.with_file_name(suffix)).unwrap();
 
-        assert_eq!(get_index_url(&crates_file, "https://github.com/rust-lang/crates.io-index", false),
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/rust-lang/crates.io-index", false),
                    Ok(("https://github.com/rust-lang/crates.io-index".to_string(), false, "crates-io".into())));
-        assert_eq!(get_index_url(&crates_file, "https://github.com/rust-lang/crates.io-index", true),
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/rust-lang/crates.io-index", true),
                    Ok(("https://index.crates.io/".to_string(), true, "crates-io".into())));
     }
 }
@@ -26,12 +27,12 @@ fn nonexistent() {
         let crates_file = prep_config("nonexistent", suffix);
         fs::remove_file(crates_file.with_file_name(suffix)).unwrap();
 
-        assert_eq!(get_index_url(&crates_file, "https://github.com/LoungeCPP/pir-8-emu", false),
-                   Err(format!("Non-crates.io registry specified and no config file found at {} or {}. Due to a Cargo limitation we will not be able to \
-                                install from there until it's given a [source.NAME] in that file!",
-                               crates_file.with_file_name("config").display(),
-                               crates_file.with_file_name("config.toml").display())
-                       .into()));
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/LoungeCPP/pir-8-emu", false),
+                   Err(Error::RegistryNotFound(format!("Non-crates.io registry specified and no config file found at {} or {}. Due to a Cargo \
+                                                        limitation we will not be able to install from there until it's given a \
+                                                        [source.NAME] in that file!",
+                                                       crates_file.with_file_name("config").display(),
+                                                       crates_file.with_file_name("config.toml").display()))));
     }
 }
 
@@ -39,18 +40,18 @@ fn nonexistent() {
 fn unknown() {
     for suffix in &["config", "config.toml"] {
         let crates_file = prep_config("unknown", suffix);
-        assert_eq!(get_index_url(&crates_file, "https://github.com/LoungeCPP/pir-8-emu", false),
-                   Err(format!("Non-crates.io registry specified and https://github.com/LoungeCPP/pir-8-emu couldn't be found in the config file at {}. \
-                                Due to a Cargo limitation we will not be able to install from there until it's given a [source.NAME] in that file!",
-                               crates_file.with_file_name(suffix).display())
-                       .into()));
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/LoungeCPP/pir-8-emu", false),
+                   Err(Error::RegistryNotFound(format!("Non-crates.io registry specified and https://github.com/LoungeCPP/pir-8-emu couldn't be \
+                                                        found in the config file at {}. Due to a Cargo limitation we will not be able to install \
+                                                        from there until it's given a [source.NAME] in that file!",
+                                                       crates_file.with_file_name(suffix).display()))));
     }
 }
 
 #[test]
 fn default() {
     for suffix in &["config", "config.toml"] {
-        assert_eq!(get_index_url(&prep_config("default", suffix), "https://github.com/rust-lang/crates.io-index", false),
+        assert_eq!(get_index_url(&sources(&prep_config("default", suffix)), "https://github.com/rust-lang/crates.io-index", false),
                    Ok(("outside-the-scope-of-this-document".to_string(), false, "tralternative".into())));
     }
 }
@@ -58,7 +59,7 @@ fn default() {
 #[test]
 fn from_alt_url() {
     for suffix in &["config", "config.toml"] {
-        assert_eq!(get_index_url(&prep_config("from_alt_url", suffix), "file:///usr/local/share/cargo", false),
+        assert_eq!(get_index_url(&sources(&prep_config("from_alt_url", suffix)), "file:///usr/local/share/cargo", false),
                    Ok(("outside-the-scope-of-this-document".to_string(), false, "tralternative".into())));
     }
 }
@@ -66,7 +67,7 @@ fn from_alt_url() {
 #[test]
 fn from_name() {
     for suffix in &["config", "config.toml"] {
-        assert_eq!(get_index_url(&prep_config("from_name", suffix), "alternative", false),
+        assert_eq!(get_index_url(&sources(&prep_config("from_name", suffix)), "alternative", false),
                    Ok(("outside-the-scope-of-this-document".to_string(), false, "tralternative".into())));
     }
 }
@@ -74,27 +75,134 @@ fn from_name() {
 #[test]
 fn sus() {
     for suffix in &["config", "config.toml"] {
-        assert_eq!(get_index_url(&prep_config("sus", suffix), "sus", false),
+        assert_eq!(get_index_url(&sources(&prep_config("sus", suffix)), "sus", false),
                    Ok(("zupa".to_string(), true, "sussy".into())));
     }
 }
 
+#[test]
+fn sparse_file() {
+    for suffix in &["config", "config.toml"] {
+        assert_eq!(get_index_url(&sources(&prep_config("sparse_file", suffix)), "file:///srv/cargo/index/", false),
+                   Ok(("file:///srv/cargo/index/".to_string(), true, "local-mirror".into())));
+        assert_eq!(get_index_url(&sources(&prep_config("sparse_file", suffix)), "local-mirror", false),
+                   Ok(("file:///srv/cargo/index/".to_string(), true, "local-mirror".into())));
+    }
+}
+
 #[test]
 fn dead_end() {
     for suffix in &["config", "config.toml"] {
         let crates_file = prep_config("dead_end", suffix);
-        assert_eq!(get_index_url(&crates_file, "dead-end", false),
-                   Err(format!("Couldn't find appropriate source URL for dead-end in {} (resolved to \"death\")",
-                               crates_file.with_file_name(suffix).display())
-                       .into()));
+        assert_eq!(get_index_url(&sources(&crates_file), "dead-end", false),
+                   Err(Error::RegistryNotFound(format!("Couldn't find appropriate source URL for dead-end in {} (resolved to \"death\")",
+                                                       crates_file.with_file_name(suffix).display()))));
+    }
+}
+
+#[test]
+fn recorded_replaced() {
+    for suffix in &["config", "config.toml"] {
+        let crates_file = prep_config("recorded_replaced", suffix);
+        assert_eq!(get_recorded_index_url(&sources(&crates_file), "https://github.com/rust-lang/crates.io-index", false),
+                   Err(Error::RegistryNotFound(format!("Source crates-io (for https://github.com/rust-lang/crates.io-index) is replaced with \
+                                                        alternative in {}, refusing to update from a different registry",
+                                                       crates_file.with_file_name(suffix).display()))));
+    }
+}
+
+#[test]
+fn recorded_not_replaced() {
+    for suffix in &["config", "config.toml"] {
+        assert_eq!(get_recorded_index_url(&sources(&prep_config("recorded_not_replaced", suffix)), "sparse+zupa", false),
+                   Ok(("zupa".to_string(), true, "sussy".into())));
+    }
+}
+
+#[test]
+fn recorded_unknown() {
+    for suffix in &["config", "config.toml"] {
+        assert!(get_recorded_index_url(&sources(&prep_config("recorded_unknown", suffix)), "https://github.com/LoungeCPP/pir-8-emu", false).is_err());
+    }
+}
+
+#[test]
+fn mirror() {
+    for suffix in &["config", "config.toml"] {
+        let crates_file = prep_config_with("mirror", suffix, MIRROR_TEST_DATA);
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/rust-lang/crates.io-index", false),
+                   Ok(("https://crates-mirror.example.com/index/".to_string(), true, "mirror-sparse".into())));
+        assert_eq!(get_index_url(&sources(&crates_file), "https://github.com/rust-lang/crates.io-index", true),
+                   Ok(("https://crates-mirror.example.com/index/".to_string(), true, "mirror-sparse".into())));
+    }
+}
+
+#[test]
+fn replacement_cycle() {
+    for suffix in &["config", "config.toml"] {
+        let crates_file = prep_config_with("replacement_cycle", suffix, MIRROR_TEST_DATA);
+        assert_eq!(get_index_url(&sources(&crates_file), "loop-a", false),
+                   Err(Error::RegistryNotFound(format!("Source loop-a (for loop-a) is replaced in a cycle in {}",
+                                                       crates_file.with_file_name(suffix).display()))));
     }
 }
 
+#[test]
+fn crates_io_index() {
+    for suffix in &["config", "config.toml"] {
+        let crates_file = prep_config("crates_io_index", suffix);
+        fs::write(crates_file.with_file_name(suffix), "").unwrap();
+        let mut rs = sources(&crates_file);
+        rs.crates_io_index = Some("sparse+https://crates-mirror.example.com/index/".to_string());
+        assert_eq!(get_index_url(&rs, "https://github.com/rust-lang/crates.io-index", false),
+                   Ok(("https://crates-mirror.example.com/index/".to_string(), true, "crates-io".into())));
+
+        rs.crates_io_index = Some("https://crates-mirror.example.com/crates.io-index".to_string());
+        assert_eq!(get_index_url(&rs, "https://github.com/rust-lang/crates.io-index", true),
+                   Ok(("https://crates-mirror.example.com/crates.io-index".to_string(), false, "crates-io".into())));
+
+        fs::remove_file(crates_file.with_file_name(suffix)).unwrap();
+        let mut rs = sources(&crates_file);
+        rs.crates_io_index = Some("sparse+https://crates-mirror.example.com/index/".to_string());
+        assert_eq!(get_index_url(&rs, "https://github.com/rust-lang/crates.io-index", false),
+                   Ok(("https://crates-mirror.example.com/index/".to_string(), true, "crates-io".into())));
+    }
+}
+
+#[test]
+fn crates_io_index_replaced() {
+    for suffix in &["config", "config.toml"] {
+        let mut rs = sources(&prep_config("crates_io_index_replaced", suffix));
+        rs.crates_io_index = Some("sparse+https://crates-mirror.example.com/index/".to_string());
+        assert_eq!(get_index_url(&rs, "https://github.com/rust-lang/crates.io-index", false),
+                   Ok(("outside-the-scope-of-this-document".to_string(), false, "tralternative".into())));
+    }
+}
+
+#[test]
+fn not_toml() {
+    let td = temp_dir().join("cargo_update-test").join("get_index_url-not_toml");
+    let _ = fs::create_dir_all(&td);
+    fs::write(td.join("config.toml"), "[source\n").unwrap();
+
+    let err = RegistrySources::load(&td.join(".crates.toml")).unwrap_err();
+    assert!(matches!(err, Error::Toml(_)), "{:?}", err);
+    assert!(err.message().starts_with(&format!("{} not TOML: ", td.join("config.toml").display())), "{}", err);
+}
+
+
+fn sources(crates_file: &Path) -> RegistrySources {
+    RegistrySources::load(crates_file).unwrap()
+}
 
 fn prep_config(subname: &str, suffix: &str) -> PathBuf {
+    prep_config_with(subname, suffix, TEST_DATA)
+}
+
+fn prep_config_with(subname: &str, suffix: &str, data: &[u8]) -> PathBuf {
     let td = temp_dir().join("cargo_update-test").join(format!("get_index_url-{}-{}", subname, suffix));
     let _ = fs::create_dir_all(&td);
 
-    fs::write(td.join(suffix), TEST_DATA).unwrap();
+    fs::write(td.join(suffix), data).unwrap();
     td.join(".crates.toml")
 }
@@ -0,0 +1,128 @@
+use cargo_update::ops::{GIT_DB_POLLED_MARKER, GitRepoPackage, git_db_repo_name};
+use git2::{BranchType, Repository, Signature, Oid};
+use std::env::temp_dir;
+use std::path::{PathBuf, Path};
+use std::fs;
+
+
+#[test]
+fn pull_version_git_ref_current() {
+    let (temp, tagged, head) = prepare("pull_version_git_ref_current");
+
+    let mut package = package_at(&temp, tagged);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, Some("v0.1.0"), false);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&tagged));
+    assert!(!package.needs_update());
+
+    let mut package = package_at(&temp, tagged);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, None, false);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&head));
+    assert!(package.needs_update());
+}
+
+#[test]
+fn pull_version_git_ref_rev() {
+    let (temp, tagged, head) = prepare("pull_version_git_ref_rev");
+
+    let mut package = package_at(&temp, head);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, Some(&tagged.to_string()[..10]), false);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&tagged));
+    assert!(package.needs_update());
+}
+
+
+#[test]
+fn pull_version_shallow() {
+    let (temp, tagged, head) = prepare("pull_version_shallow");
+
+    let mut package = package_at(&temp, tagged);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, None, true);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&head));
+    assert!(package.needs_update());
+
+    let mut package = package_at(&temp, tagged);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, Some("v0.1.0"), true);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&tagged));
+    assert!(!package.needs_update());
+}
+
+#[test]
+fn describe() {
+    let (temp, tagged, head) = prepare("describe");
+
+    let mut package = package_at(&temp, tagged);
+    assert_eq!(package.describe(temp.join("clones"), temp.join("db"), tagged), None);
+
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, None, false);
+    assert_eq!(package.describe(temp.join("clones"), temp.join("db"), tagged).as_deref(), Some("v0.1.0"));
+    assert_eq!(package.describe(temp.join("clones"), temp.join("db"), head),
+               Some(format!("v0.1.0-1-g{}", &head.to_string()[..7])));
+    assert_eq!(package.describe(temp.join("clones"), temp.join("db"), Oid::zero()), None);
+
+    Repository::open(temp.join("clones").join("synthetic")).unwrap().tag_delete("v0.1.0").unwrap();
+    assert_eq!(package.describe(temp.join("clones"), temp.join("db"), head), None);
+}
+
+#[test]
+fn pull_version_cargo_db() {
+    let (temp, tagged, head) = prepare("pull_version_cargo_db");
+
+    let mut package = package_at(&temp, tagged);
+    let db = Repository::init_bare(temp.join("db").join(git_db_repo_name(&package.url).unwrap())).unwrap();
+    let db_head = db.head().ok().and_then(|h| h.target());
+
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, None, false);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&head));
+    assert!(!temp.join("clones").join("synthetic").exists());
+
+    let mut package = package_at(&temp, head);
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, Some("v0.1.0"), false);
+    assert_eq!(package.newest_id.as_ref().ok(), Some(&tagged));
+    assert!(!temp.join("clones").join("synthetic").exists());
+
+    // Only remote-tracking refs were fetched into
+    assert_eq!(db.head().ok().and_then(|h| h.target()), db_head);
+    assert_eq!(db.branches(Some(BranchType::Local)).unwrap().count(), 0);
+    assert_eq!(db.find_reference("refs/remotes/origin/HEAD").unwrap().target(), Some(head));
+    assert!(db.path().join(GIT_DB_POLLED_MARKER).is_file());
+}
+
+#[test]
+fn pull_version_ssh_cli_fallback() {
+    let temp = temp_dir().join("cargo_update-test").join("git_repo_package-pull_version_ssh_cli_fallback");
+    let _ = fs::remove_dir_all(&temp);
+    fs::create_dir_all(&temp).unwrap();
+
+    // Nothing listens on the discard port, so libgit2 fails to connect, and then so does the git CLI
+    let ident = "synthetic 0.1.0 (git+ssh://git@127.0.0.1:9/synthetic#eb231b3e70b87875df4bdd1974d5e94704024d70)";
+
+    let mut package = GitRepoPackage::parse(ident, vec![]).unwrap();
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, true, None, false);
+    let err = package.newest_id.unwrap_err();
+    assert!(err.message().contains("(with libgit2), then"), "{}", err);
+    assert!(err.message().ends_with("(retried with the git CLI)"), "{}", err);
+
+    let mut package = GitRepoPackage::parse(ident, vec![]).unwrap();
+    package.pull_version(temp.join("clones"), temp.join("db"), None, false, false, None, false);
+    let err = package.newest_id.unwrap_err();
+    assert!(!err.message().contains("git CLI"), "{}", err);
+}
+
+fn prepare(subname: &str) -> (PathBuf, Oid, Oid) {
+    let temp = temp_dir().join("cargo_update-test").join(format!("git_repo_package-{}", subname));
+    let _ = fs::remove_dir_all(&temp);
+    fs::create_dir_all(&temp).unwrap();
+
+    let repo = Repository::init(temp.join("upstream")).unwrap();
+    let sig = Signature::now("cargo-update", "cargo-update@example.com").unwrap();
+    let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+    let tagged = repo.commit(Some("HEAD"), &sig, &sig, "Tagged", &tree, &[]).unwrap();
+    repo.tag_lightweight("v0.1.0", &repo.find_object(tagged, None).unwrap(), false).unwrap();
+    let head = repo.commit(Some("HEAD"), &sig, &sig, "Latest", &tree, &[&repo.find_commit(tagged).unwrap()]).unwrap();
+
+    (temp, tagged, head)
+}
+
+fn package_at(temp: &Path, id: Oid) -> GitRepoPackage {
+    GitRepoPackage::parse(&format!("synthetic 0.1.0 (git+file://{}#{})", temp.join("upstream").display(), id), vec![]).unwrap()
+}
@@ -0,0 +1,43 @@
+use cargo_update::ops::{InstalledPackages, installed_registry_packages, installed_git_repo_packages, installed_path_packages, installed_packages};
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::io::Write;
+
+
+static CRATES: &[u8] = include_bytes!("../../test-data/.cargo-crates.toml");
+
+
+#[test]
+fn existent() {
+    let td = temp_dir().join("cargo_update-test").join("installed_packages-existent");
+    let _ = fs::create_dir_all(&td);
+    File::create(td.join(".crates.toml")).unwrap().write_all(CRATES).unwrap();
+
+    let installed = installed_packages(&td);
+    assert_eq!(installed, InstalledPackages::read(&td.join(".crates.toml")));
+    assert_eq!(installed.registry, installed_registry_packages(&td.join(".crates.toml")));
+    assert_eq!(installed.git, installed_git_repo_packages(&td.join(".crates.toml")));
+    assert_eq!(installed.path, installed_path_packages(&td.join(".crates.toml")));
+    assert!(!installed.registry.is_empty());
+    assert!(!installed.git.is_empty());
+}
+
+#[test]
+fn install_root() {
+    let td = temp_dir().join("cargo_update-test").join("installed_packages-install_root");
+    let root = td.join("root");
+    let _ = fs::create_dir_all(&root);
+    File::create(root.join(".crates.toml")).unwrap().write_all(CRATES).unwrap();
+    fs::write(td.join("config.toml"), format!("[install]\nroot = {:?}\n", root.display().to_string())).unwrap();
+
+    assert_eq!(installed_packages(&td), InstalledPackages::read(&root.join(".crates.toml")));
+    assert!(!installed_packages(&td).registry.is_empty());
+}
+
+#[test]
+fn non_existent() {
+    let td = temp_dir().join("cargo_update-test").join("installed_packages-nonexistent");
+    let _ = fs::create_dir_all(&td);
+
+    assert_eq!(installed_packages(&td), InstalledPackages::default());
+}
@@ -0,0 +1,36 @@
+use cargo_update::ops::{PathPackage, installed_path_packages};
+use semver::Version as Semver;
+use std::fs::{self, File};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::io::Write;
+
+
+static CRATES: &[u8] = include_bytes!("../../test-data/.cargo-crates.toml");
+
+
+#[cfg(unix)]
+#[test]
+fn existent() {
+    let mut td = temp_dir().join("cargo_update-test").join("installed_path_packages-existent");
+    let _ = fs::create_dir_all(&td);
+    td.push(".crates.toml");
+
+    File::create(&td).unwrap().write_all(CRATES).unwrap();
+
+    assert_eq!(installed_path_packages(&td),
+               vec![PathPackage {
+                        name: "treesize-local".to_string(),
+                        version: Semver::parse("0.2.1").unwrap(),
+                        path: PathBuf::from("/home/nabijaczleweli/treesize-rs"),
+                        executables: vec!["treesize-local.exe".to_string()],
+                    }]);
+}
+
+#[test]
+fn non_existent() {
+    let td = temp_dir().join("cargo_update-test").join("installed_path_packages-nonexistent");
+    let _ = fs::create_dir_all(&td);
+
+    assert_eq!(installed_path_packages(&td.join(".crates.toml")), vec![]);
+}
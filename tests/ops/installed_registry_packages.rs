@@ -6,6 +6,7 @@ use std::io::Write;
 
 
 static CRATES: &[u8] = include_bytes!("../../test-data/.cargo-crates.toml");
+static CRATES_REGISTRY_CLASH: &[u8] = include_bytes!("../../test-data/.cargo-crates-registry-clash.toml");
 
 
 #[test]
@@ -23,7 +24,11 @@ fn existent() {
                         version: Some(Semver::parse("0.2.0").unwrap()),
                         newest_version: None,
                         alternative_version: None,
-                        max_version: None,
+                        version_req: None,
+                        newest_satisfying_version: None,
+                        pinned_version: None,
+                        versions_behind: None,
+                        current_yanked: false,
                         executables: vec!["cargo-outdated.exe".to_string()],
                     },
                     RegistryPackage {
@@ -32,7 +37,11 @@ fn existent() {
                         version: Some(Semver::parse("1.2.10").unwrap()),
                         newest_version: None,
                         alternative_version: None,
-                        max_version: None,
+                        version_req: None,
+                        newest_satisfying_version: None,
+                        pinned_version: None,
+                        versions_behind: None,
+                        current_yanked: false,
                         executables: vec!["racer.exe".to_string()],
                     },
                     RegistryPackage {
@@ -41,11 +50,38 @@ fn existent() {
                         version: Some(Semver::parse("0.6.2").unwrap()),
                         newest_version: None,
                         alternative_version: None,
-                        max_version: None,
+                        version_req: None,
+                        newest_satisfying_version: None,
+                        pinned_version: None,
+                        versions_behind: None,
+                        current_yanked: false,
                         executables: vec!["cargo-fmt.exe".to_string(), "rustfmt.exe".to_string()],
                     }]);
 }
 
+#[test]
+fn registry_clash() {
+    let mut td = temp_dir().join("cargo_update-test").join("installed_registry_packages-registry_clash");
+    let _ = fs::create_dir_all(&td);
+    td.push(".crates.toml");
+
+    File::create(&td).unwrap().write_all(CRATES_REGISTRY_CLASH).unwrap();
+
+    assert_eq!(installed_registry_packages(&td)
+                   .into_iter()
+                   .map(|p| (p.name, p.registry, p.version.unwrap().to_string(), p.executables))
+                   .collect::<Vec<_>>(),
+               vec![("cargo-outdated".to_string(),
+                     "https://github.com/rust-lang/crates.io-index".to_string(),
+                     "0.2.0".to_string(),
+                     vec!["cargo-outdated.exe".to_string()]),
+                    ("racer".to_string(), "https://github.com/rust-lang/crates.io-index".to_string(), "1.2.10".to_string(), vec!["racer.exe".to_string()]),
+                    ("racer".to_string(),
+                     "https://dl.cloudsmith.io/public/racer/racer/cargo/index.git".to_string(),
+                     "2.0.0".to_string(),
+                     vec!["racer-private.exe".to_string()])]);
+}
+
 #[test]
 fn non_existent() {
     let td = temp_dir().join("cargo_update-test").join("installed_registry_packages-nonexistent");
@@ -53,3 +89,22 @@ fn non_existent() {
 
     assert_eq!(installed_registry_packages(&td.join(".crates.toml")), vec![]);
 }
+
+#[test]
+fn many() {
+    let mut td = temp_dir().join("cargo_update-test").join("installed_registry_packages-many");
+    let _ = fs::create_dir_all(&td);
+    td.push(".crates.toml");
+
+    let mut crates = File::create(&td).unwrap();
+    writeln!(crates, "[v1]").unwrap();
+    for i in 0..20000 {
+        writeln!(crates, "\"synthetic-{} 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)\" = [\"synthetic-{}\"]", i, i).unwrap();
+        writeln!(crates, "\"synthetic-{} 0.2.0 (registry+https://github.com/rust-lang/crates.io-index)\" = [\"synthetic-{}\"]", i, i).unwrap();
+    }
+    drop(crates);
+
+    let packages = installed_registry_packages(&td);
+    assert_eq!(packages.len(), 20000);
+    assert!(packages.iter().all(|p| p.version == Some(Semver::parse("0.2.0").unwrap())));
+}
@@ -0,0 +1,42 @@
+use cargo_update::ops::is_breaking_update;
+use semver::Version as Semver;
+
+
+#[test]
+fn major() {
+    assert!(is_breaking_update(&v("1.7.2"), &v("2.0.0")));
+    assert!(is_breaking_update(&v("1.7.2"), &v("3.1.0")));
+    assert!(!is_breaking_update(&v("1.7.2"), &v("1.8.0")));
+    assert!(!is_breaking_update(&v("1.7.2"), &v("1.7.3")));
+}
+
+#[test]
+fn zero_minor() {
+    assert!(is_breaking_update(&v("0.7.2"), &v("0.8.0")));
+    assert!(is_breaking_update(&v("0.7.2"), &v("1.0.0")));
+    assert!(!is_breaking_update(&v("0.7.2"), &v("0.7.9")));
+}
+
+#[test]
+fn zero_zero_patch() {
+    assert!(is_breaking_update(&v("0.0.3"), &v("0.0.4")));
+    assert!(is_breaking_update(&v("0.0.3"), &v("0.1.0")));
+    assert!(!is_breaking_update(&v("0.0.3"), &v("0.0.3")));
+}
+
+#[test]
+fn downdate() {
+    assert!(!is_breaking_update(&v("2.0.0"), &v("1.9.0")));
+    assert!(!is_breaking_update(&v("0.8.0"), &v("0.7.2")));
+}
+
+#[test]
+fn prerelease() {
+    assert!(is_breaking_update(&v("1.7.2"), &v("2.0.0-rc.1")));
+    assert!(!is_breaking_update(&v("2.0.0-rc.1"), &v("2.0.0")));
+}
+
+
+fn v(s: &str) -> Semver {
+    Semver::parse(s).unwrap()
+}
@@ -0,0 +1,45 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use cargo_update::ops::LogWriter;
+use std::io::Write;
+use std::sync::Mutex;
+use log;
+
+
+static LOGGED: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        LOGGED.lock().unwrap().push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+
+#[test]
+fn lines() {
+    let _ = log::set_logger(&Capture);
+    log::set_max_level(LevelFilter::Trace);
+
+    {
+        let mut out = LogWriter::new(Level::Debug);
+        write!(out, "    Polling registry 'https://index.crates.io/'").unwrap();
+        out.flush().unwrap();
+        write!(out, "..").unwrap();
+        out.flush().unwrap();
+        writeln!(out).unwrap();
+        write!(out, "\r(1/2)\r(2/2)\n\ntrailing").unwrap();
+    }
+
+    assert_eq!(*LOGGED.lock().unwrap(),
+               [(Level::Debug, "    Polling registry 'https://index.crates.io/'..".to_string()),
+                (Level::Debug, "(1/2)".to_string()),
+                (Level::Debug, "(2/2)".to_string()),
+                (Level::Debug, "trailing".to_string())]);
+}
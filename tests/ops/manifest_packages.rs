@@ -0,0 +1,39 @@
+use cargo_update::ops::{manifest_locked_packages, manifest_packages};
+use semver::VersionReq as SemverReq;
+use std::collections::BTreeSet;
+use std::fs;
+
+
+#[test]
+fn sample() {
+    assert_eq!(manifest_packages(&fs::read_to_string("test-data/tool-manifest.toml").unwrap()),
+               Ok(vec![("cargo-nextest".to_string(),
+                        Some(SemverReq::parse("<=0.9.57").unwrap()),
+                        "https://github.com/rust-lang/crates.io-index".to_string(),
+                        false),
+                       ("cargo-outdated".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                       ("cargo-update".to_string(),
+                        Some(SemverReq::parse("<=16.1.0").unwrap()),
+                        "https://github.com/rust-lang/crates.io-index".to_string(),
+                        false),
+                       ("racer".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false)]));
+}
+
+#[test]
+fn no_tools() {
+    assert_eq!(manifest_packages("[package]\nname = \"example\"\n"), Ok(vec![]));
+}
+
+#[test]
+fn invalid_version() {
+    assert!(manifest_packages("[workspace.metadata.cargo-update]\ncargo-update = \"16\"\n").is_err());
+    assert!(manifest_packages("[workspace.metadata.cargo-update]\ncargo-update = 16\n").is_err());
+}
+
+#[test]
+fn locked() {
+    assert_eq!(manifest_locked_packages(&fs::read_to_string("test-data/tool-manifest.toml").unwrap()),
+               Ok(vec!["cargo-outdated".to_string()].into_iter().collect()));
+    assert_eq!(manifest_locked_packages("[workspace.metadata.cargo-update]\ncargo-update = { locked = false }\n"), Ok(BTreeSet::new()));
+    assert!(manifest_locked_packages("[workspace.metadata.cargo-update]\ncargo-update = { locked = \"yes\" }\n").is_err());
+}
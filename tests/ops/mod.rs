@@ -3,11 +3,40 @@ use semver::Version as Semver;
 use std::fs;
 
 mod installed_registry_packages;
+mod installed_path_packages;
+mod installed_packages;
 mod package_filter_element;
 #[cfg(all(target_pointer_width="64", target_endian="little"))] // https://github.com/nabijaczleweli/cargo-update/issues/235
 mod assert_index_path;
 mod registry_package;
 mod get_index_url;
+mod package_name_has_prefix;
+mod package_name_glob_matches;
+mod package_has_binaries;
+mod manifest_packages;
+mod update_index;
+mod netrc_credentials;
+mod read_sparse_cache;
+mod versions_behind;
+mod shell_command;
+mod find_rust_toolchain;
+mod command_line;
+mod update_summary;
+mod is_breaking_update;
+mod plan_updates;
+mod parse_any;
+mod package_config;
+mod clean_sparse_cache;
+mod cargo_home_mismatch;
+mod git_repo_package;
+mod toolchain_installed;
+mod proxy_credentials;
+mod proxy_excluded;
+mod program_exists;
+mod orphaned_git_clones;
+mod snapshot;
+#[cfg(feature = "logging")]
+mod log_writer;
 
 
 #[test]
@@ -18,9 +47,9 @@ fn intersect_packages() {
                                                                 vec!["cc".to_string()])
                                              .unwrap(),
                                          RegistryPackage::parse("racer 1.2.10 (registry+file:///usr/local/share/cargo)", vec!["r".to_string()]).unwrap()],
-                                       &[("cargo-count".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string()),
-                                         ("racer".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string()),
-                                         ("checksums".to_string(), None, "file:///usr/local/share/cargo".to_string())],
+                                       &[("cargo-count".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                                         ("racer".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                                         ("checksums".to_string(), None, "file:///usr/local/share/cargo".to_string(), false)],
                                        false,
                                        &[]),
                vec![RegistryPackage::parse("cargo-count 0.2.2 (registry+https://github.com/rust-lang/crates.io-index)",
@@ -29,15 +58,44 @@ fn intersect_packages() {
                     RegistryPackage::parse("racer 1.2.10 (registry+file:///usr/local/share/cargo)", vec!["r".to_string()]).unwrap()]);
 }
 
+#[test]
+fn intersect_packages_many() {
+    let installed: Vec<_> = (0..20000)
+        .map(|i| RegistryPackage::parse(&format!("synthetic-{} 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)", i), vec![]).unwrap())
+        .collect();
+    let to_update: Vec<_> = (10000..30000)
+        .map(|i| (format!("synthetic-{}", i), None, "https://github.com/rust-lang/crates.io-index".to_string(), false))
+        .collect();
+
+    let intersected = ops::intersect_packages(&installed, &to_update, true, &[]);
+    assert_eq!(intersected.len(), 20000);
+    assert_eq!(intersected.iter().filter(|p| p.version.is_some()).count(), 10000);
+    assert_eq!(intersected[0].name, "synthetic-10000");
+    assert_eq!(intersected[19999].name, "synthetic-29999");
+}
+
 #[test]
 fn crate_versions() {
     assert_eq!(ops::crate_versions(&fs::read("test-data/checksums-versions.json").unwrap()).unwrap(),
-               vec![Semver::parse("0.2.0").unwrap(),
-                    Semver::parse("0.2.1").unwrap(),
-                    Semver::parse("0.3.0").unwrap(),
-                    Semver::parse("0.4.0").unwrap(),
-                    Semver::parse("0.4.1").unwrap(),
-                    Semver::parse("0.5.0").unwrap(),
-                    Semver::parse("0.5.1").unwrap(),
-                    Semver::parse("0.5.2").unwrap()]);
+               vec![(Semver::parse("0.1.0").unwrap(), None, true),
+                    (Semver::parse("0.1.1").unwrap(), None, true),
+                    (Semver::parse("0.2.0").unwrap(), None, false),
+                    (Semver::parse("0.2.1").unwrap(), None, false),
+                    (Semver::parse("0.3.0").unwrap(), None, false),
+                    (Semver::parse("0.4.0").unwrap(), None, false),
+                    (Semver::parse("0.4.1").unwrap(), None, false),
+                    (Semver::parse("0.5.0").unwrap(), None, false),
+                    (Semver::parse("0.5.1").unwrap(), None, false),
+                    (Semver::parse("0.5.2").unwrap(), None, false)]);
+}
+
+#[test]
+fn crate_versions_rust_version() {
+    assert_eq!(ops::crate_versions(b"{\"name\":\"synthetic\",\"vers\":\"1.0.0\",\"yanked\":false}\n\
+                                     {\"name\":\"synthetic\",\"vers\":\"1.1.0\",\"yanked\":false,\"rust_version\":\"1.70\"}\n\
+                                     {\"name\":\"synthetic\",\"vers\":\"1.2.0\",\"yanked\":true,\"rust_version\":\"1.75\"}\n")
+                   .unwrap(),
+               vec![(Semver::parse("1.0.0").unwrap(), None, false),
+                    (Semver::parse("1.1.0").unwrap(), Some(Semver::new(1, 70, 0)), false),
+                    (Semver::parse("1.2.0").unwrap(), Some(Semver::new(1, 75, 0)), true)]);
 }
@@ -0,0 +1,53 @@
+use cargo_update::ops::{netrc_credentials, basic_authorization};
+
+
+static NETRC: &str = "# corporate mirrors\n\
+                      machine mirror.example.com\n\
+                      \tlogin ci-bot\n\
+                      \tpassword s3cr3t\n\
+                      \n\
+                      machine git.example.com login dev account ops password hunter2\n\
+                      macdef init\n\
+                      machine evil.example.com login evil password evil\n\
+                      \n\
+                      default login anonymous password guest\n";
+
+
+#[test]
+fn machine() {
+    let (user, password) = netrc_credentials(NETRC, "mirror.example.com").unwrap();
+    assert_eq!((&user[..], &password[..]), ("ci-bot", "s3cr3t"));
+    assert_eq!(basic_authorization(&user, &password), "Basic Y2ktYm90OnMzY3IzdA==");
+}
+
+#[test]
+fn account() {
+    assert_eq!(netrc_credentials(NETRC, "git.example.com"), Some(("dev".to_string(), "hunter2".to_string())));
+}
+
+#[test]
+fn case_insensitive() {
+    assert_eq!(netrc_credentials(NETRC, "MIRROR.example.com"), Some(("ci-bot".to_string(), "s3cr3t".to_string())));
+}
+
+#[test]
+fn macdef() {
+    assert_eq!(netrc_credentials(NETRC, "evil.example.com"), Some(("anonymous".to_string(), "guest".to_string())));
+}
+
+#[test]
+fn default() {
+    let (user, password) = netrc_credentials(NETRC, "index.crates.io").unwrap();
+    assert_eq!(basic_authorization(&user, &password), "Basic YW5vbnltb3VzOmd1ZXN0");
+}
+
+#[test]
+fn no_match() {
+    assert_eq!(netrc_credentials("machine mirror.example.com login user password token\n", "index.crates.io"), None);
+}
+
+#[test]
+fn incomplete() {
+    assert_eq!(netrc_credentials("machine mirror.example.com login user\n", "mirror.example.com"), None);
+    assert_eq!(netrc_credentials("login user password token\n", "mirror.example.com"), None);
+}
@@ -0,0 +1,70 @@
+use cargo_update::ops::{GIT_DB_POLLED_MARKER, GitRepoPackage, git_db_repo_name, orphaned_git_clones, dir_size};
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+fn alacritty() -> GitRepoPackage {
+    GitRepoPackage::parse("alacritty 0.1.0 (git+https://github.com/jwilm/alacritty#eb231b3e70b87875df4bdd1974d5e94704024d70)",
+                          vec!["alacritty".to_string()])
+        .unwrap()
+}
+
+fn prep(sub: &str) -> (PathBuf, PathBuf) {
+    let td = temp_dir().join("cargo_update-test").join(format!("orphaned_git_clones-{}", sub));
+    let _ = fs::remove_dir_all(&td);
+    (td.join("cargo-update"), td.join("git").join("db"))
+}
+
+
+#[test]
+fn orphans() {
+    let (temp_dir, git_db_dir) = prep("orphans");
+    let alacritty_db = git_db_dir.join(git_db_repo_name("https://github.com/jwilm/alacritty").unwrap());
+    let treesize_db = git_db_dir.join(git_db_repo_name("https://github.com/melak47/treesize-rs").unwrap());
+    for dir in &[temp_dir.join("alacritty"), temp_dir.join("treesize"), alacritty_db, treesize_db.clone()] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(treesize_db.join(GIT_DB_POLLED_MARKER), "").unwrap();
+    // Not clones
+    fs::write(temp_dir.join("output.log"), "").unwrap();
+    fs::write(git_db_dir.join("CACHEDIR.TAG"), "").unwrap();
+
+    assert_eq!(orphaned_git_clones(&temp_dir, &git_db_dir, &[alacritty()]), vec![(temp_dir.join("treesize"), true), (treesize_db, true)]);
+}
+
+#[test]
+fn not_polled() {
+    let (temp_dir, git_db_dir) = prep("not_polled");
+    let serde_db = git_db_dir.join(git_db_repo_name("https://github.com/serde-rs/serde").unwrap());
+    fs::create_dir_all(&serde_db).unwrap();
+
+    assert_eq!(orphaned_git_clones(&temp_dir, &git_db_dir, &[alacritty()]), vec![(serde_db, false)]);
+}
+
+#[test]
+fn nothing_installed() {
+    let (temp_dir, git_db_dir) = prep("nothing_installed");
+    fs::create_dir_all(temp_dir.join("alacritty")).unwrap();
+
+    assert_eq!(orphaned_git_clones(&temp_dir, &git_db_dir, &[]), vec![(temp_dir.join("alacritty"), true)]);
+}
+
+#[test]
+fn non_existent() {
+    let (temp_dir, git_db_dir) = prep("non_existent");
+
+    assert_eq!(orphaned_git_clones(&temp_dir, &git_db_dir, &[alacritty()]), Vec::<(PathBuf, bool)>::new());
+}
+
+#[test]
+fn size() {
+    let (temp_dir, _) = prep("size");
+    let clone = temp_dir.join("alacritty");
+    fs::create_dir_all(clone.join("objects").join("pack")).unwrap();
+    fs::write(clone.join("HEAD"), "ref: refs/heads/master\n").unwrap();
+    fs::write(clone.join("objects").join("pack").join("pack-0.pack"), vec![0u8; 4096]).unwrap();
+
+    assert_eq!(dir_size(&clone), 23 + 4096);
+    assert_eq!(dir_size(&clone.join("HEAD")), 23);
+}
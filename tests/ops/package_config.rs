@@ -0,0 +1,293 @@
+use cargo_update::ops::{ConfigOperation, PackageConfig};
+use std::collections::BTreeMap;
+use std::env::temp_dir;
+use std::path::Path;
+use std::fs;
+
+
+#[test]
+fn cargo_args_target() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetTargetTriple("x86_64-unknown-linux-musl".to_string())]).cargo_args(&[] as &[&str]),
+               ["install", "-f", "--target", "x86_64-unknown-linux-musl"]);
+}
+
+#[test]
+fn cargo_args_target_removed() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetTargetTriple("x86_64-unknown-linux-musl".to_string()), ConfigOperation::RemoveTargetTriple])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f"]);
+}
+
+#[test]
+fn cargo_args_extra() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::AddInstallArg("--config".to_string()),
+                                     ConfigOperation::AddInstallArg("net.retry=5".to_string()),
+                                     ConfigOperation::SetTargetTriple("x86_64-unknown-linux-musl".to_string())])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f", "--target", "x86_64-unknown-linux-musl", "--config", "net.retry=5"]);
+}
+
+#[test]
+fn cargo_args_extra_cleared() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::AddInstallArg("--config".to_string()), ConfigOperation::ClearInstallArgs])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f"]);
+}
+
+#[test]
+fn cargo_args_all_features() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::DefaultFeatures(false),
+                                     ConfigOperation::AddFeature("serde".to_string()),
+                                     ConfigOperation::SetAllFeatures(true)])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f", "--all-features"]);
+}
+
+#[test]
+fn cargo_args_all_features_unset() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::AddFeature("serde".to_string()),
+                                     ConfigOperation::SetAllFeatures(true),
+                                     ConfigOperation::SetAllFeatures(false)])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f", "--features", "serde "]);
+}
+
+#[test]
+fn cargo_args_offline() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetEnforceLock(true), ConfigOperation::SetOffline(true)]).cargo_args(&[] as &[&str]),
+               ["install", "-f", "--locked", "--offline"]);
+}
+
+#[test]
+fn cargo_args_frozen() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetEnforceLock(true), ConfigOperation::SetOffline(true), ConfigOperation::SetFrozen(true)])
+                   .cargo_args(&[] as &[&str]),
+               ["install", "-f", "--frozen"]);
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetFrozen(true), ConfigOperation::SetFrozen(false)]).cargo_args(&[] as &[&str]),
+               ["install", "-f"]);
+}
+
+#[test]
+fn cargo_args_jobs() {
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetJobs(2)]).cargo_args(&[] as &[&str]), ["install", "-f", "-j", "2"]);
+    assert_eq!(PackageConfig::from(&[ConfigOperation::SetJobs(2), ConfigOperation::RemoveJobs]).cargo_args(&[] as &[&str]),
+               ["install", "-f"]);
+}
+
+#[test]
+fn jobs_roundtrip() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-jobs_roundtrip");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+
+    let mut configuration = BTreeMap::new();
+    configuration.insert("cargo-update".to_string(), PackageConfig::from(&[ConfigOperation::SetJobs(3)]));
+    PackageConfig::write(&configuration, &config_file).unwrap();
+
+    let read = PackageConfig::read(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(read, configuration);
+    assert_eq!(read["cargo-update"].jobs, Some(3));
+}
+
+#[test]
+fn frozen_offline_roundtrip() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-frozen_offline_roundtrip");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+
+    let mut configuration = BTreeMap::new();
+    configuration.insert("cargo-update".to_string(),
+                         PackageConfig::from(&[ConfigOperation::SetFrozen(true), ConfigOperation::SetOffline(false)]));
+    PackageConfig::write(&configuration, &config_file).unwrap();
+
+    let read = PackageConfig::read(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(read, configuration);
+    assert_eq!(read["cargo-update"].frozen, Some(true));
+    assert_eq!(read["cargo-update"].offline, Some(false));
+}
+
+#[test]
+fn post_install() {
+    let cfg = PackageConfig::from(&[ConfigOperation::SetPostInstall("rustup component add rust-src".to_string())]);
+    assert_eq!(cfg.post_install.as_deref(), Some("rustup component add rust-src"));
+    assert_eq!(cfg.cargo_args(&[] as &[&str]), ["install", "-f"]);
+
+    let mut cfg = cfg;
+    cfg.execute_operations(&[ConfigOperation::ClearPostInstall]);
+    assert_eq!(cfg, PackageConfig::default());
+}
+
+#[test]
+fn rustflags() {
+    let cfg = PackageConfig::from(&[ConfigOperation::SetRustflags("-C  target-cpu=native ".to_string()),
+                                    ConfigOperation::AddRustflag("-Dwarnings".to_string())]);
+    assert_eq!(cfg.rustflags, ["-C", "target-cpu=native", "-Dwarnings"]);
+    assert_eq!(cfg.rustflags_value(Some("-Copt-level=s")).as_deref(), Some("-C target-cpu=native -Dwarnings"));
+
+    let mut cfg = cfg;
+    cfg.execute_operations(&[ConfigOperation::ClearRustflags]);
+    assert_eq!(cfg, PackageConfig::default());
+    assert_eq!(cfg.rustflags_value(Some("-Copt-level=s")), None);
+}
+
+#[test]
+fn rustflags_append() {
+    let cfg = PackageConfig::from(&[ConfigOperation::AddRustflag("-Dwarnings".to_string()), ConfigOperation::SetAppendRustflags(true)]);
+    assert_eq!(cfg.rustflags_value(Some(" -Copt-level=s ")).as_deref(), Some("-Copt-level=s -Dwarnings"));
+    assert_eq!(cfg.rustflags_value(Some("")).as_deref(), Some("-Dwarnings"));
+
+    let mut env_cfg = cfg.clone();
+    env_cfg.execute_operations(&[ConfigOperation::SetEnvironment("RUSTFLAGS".to_string(), "-Cdebuginfo=0".to_string())]);
+    assert_eq!(env_cfg.rustflags_value(Some("-Copt-level=s")).as_deref(), Some("-Cdebuginfo=0 -Dwarnings"));
+
+    let mut env_cfg = cfg;
+    env_cfg.execute_operations(&[ConfigOperation::ClearEnvironment("RUSTFLAGS".to_string())]);
+    assert_eq!(env_cfg.rustflags_value(Some("-Copt-level=s")).as_deref(), Some("-Dwarnings"));
+}
+
+#[test]
+fn rustflags_roundtrip() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-rustflags_roundtrip");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+
+    let mut configuration = BTreeMap::new();
+    configuration.insert("cargo-update".to_string(),
+                         PackageConfig::from(&[ConfigOperation::AddRustflag("-Ctarget-cpu=native".to_string()),
+                                               ConfigOperation::SetAppendRustflags(true)]));
+    PackageConfig::write(&configuration, &config_file).unwrap();
+
+    let read = PackageConfig::read(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(read, configuration);
+    assert_eq!(read["cargo-update"].rustflags, ["-Ctarget-cpu=native"]);
+    assert_eq!(read["cargo-update"].append_rustflags, Some(true));
+}
+
+#[test]
+fn install_cargo() {
+    let cfg = PackageConfig::from(&[ConfigOperation::SetInstallCargo("cross".to_string())]);
+    assert_eq!(cfg.install_cargo.as_deref(), Some(Path::new("cross")));
+    assert_eq!(cfg.cargo_args(&[] as &[&str]), ["install", "-f"]);
+    assert_eq!(PackageConfig::default().inherit(&cfg).install_cargo.as_deref(), Some(Path::new("cross")));
+
+    let mut cfg = cfg;
+    cfg.execute_operations(&[ConfigOperation::RemoveInstallCargo]);
+    assert_eq!(cfg, PackageConfig::default());
+}
+
+#[test]
+fn crates2_backfill() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-crates2_backfill");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+    let crates2_file = td.join(".crates2.json");
+
+    fs::write(&config_file, "[checksums]\ndefault_features = true\nfeatures = [\"cli\"]\n").unwrap();
+    fs::write(&crates2_file,
+              r#"{"installs":{
+                  "checksums 0.5.2 (registry+https://github.com/rust-lang/crates.io-index)":
+                      {"features":["serde"],"all_features":false,"no_default_features":true,"profile":"release"},
+                  "alacritty 0.1.0 (git+https://github.com/jwilm/alacritty#eb231b3e70b87875df4bdd1974d5e94704024d70)":
+                      {"features":[],"all_features":true,"no_default_features":false,"profile":"release"},
+                  "treesize 0.2.1 (path+file:///home/nabijaczleweli/treesize-rs)":
+                      {"features":["log"],"all_features":false,"no_default_features":true,"profile":"dev"}
+              }}"#)
+        .unwrap();
+
+    let read = PackageConfig::read(&config_file, &crates2_file).unwrap();
+    assert_eq!(read["checksums"].cargo_args(&[] as &[&str]), ["install", "-f", "--features", "cli "]);
+    assert_eq!(read["alacritty"].cargo_args(&[] as &[&str]), ["install", "-f", "--all-features"]);
+    assert_eq!(read["treesize"].cargo_args(&[] as &[&str]), ["install", "-f", "--no-default-features", "--features", "log ", "--debug"]);
+
+    let mut written = read.clone();
+    written.remove("checksums");
+    PackageConfig::write(&written, &config_file).unwrap();
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), "");
+}
+
+#[test]
+fn hold_roundtrip() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-hold_roundtrip");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+
+    let mut configuration = BTreeMap::new();
+    configuration.insert("cargo-update".to_string(), PackageConfig::from(&[ConfigOperation::SetHold(true)]));
+    configuration.insert("treesize".to_string(), PackageConfig::from(&[ConfigOperation::SetHold(false)]));
+    PackageConfig::write(&configuration, &config_file).unwrap();
+
+    let read = PackageConfig::read(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(read, configuration);
+    assert_eq!(read["cargo-update"].hold, Some(true));
+    assert_eq!(read["treesize"].hold, Some(false));
+    assert_eq!(PackageConfig::default().inherit(&read["cargo-update"]).hold, Some(true));
+    assert_eq!(read["treesize"].inherit(&read["cargo-update"]).hold, Some(false));
+}
+
+#[test]
+fn inherit() {
+    let base = PackageConfig::from(&[ConfigOperation::SetToolchain("nightly".to_string()),
+                                     ConfigOperation::DefaultFeatures(false),
+                                     ConfigOperation::AddFeature("serde".to_string()),
+                                     ConfigOperation::SetBuildProfile("dev".into()),
+                                     ConfigOperation::AddInstallArg("--locked".to_string()),
+                                     ConfigOperation::SetEnvironment("RUSTC_WRAPPER".to_string(), "sccache".to_string()),
+                                     ConfigOperation::SetEnvironment("CC".to_string(), "gcc".to_string())]);
+    let cfg = PackageConfig::from(&[ConfigOperation::SetToolchain("stable".to_string()),
+                                    ConfigOperation::AddFeature("log".to_string()),
+                                    ConfigOperation::AddInstallArg("--offline".to_string()),
+                                    ConfigOperation::SetEnforceLock(true),
+                                    ConfigOperation::ClearEnvironment("CC".to_string())]);
+    assert_eq!(cfg.inherit(&base),
+               PackageConfig::from(&[ConfigOperation::SetToolchain("stable".to_string()),
+                                     ConfigOperation::DefaultFeatures(false),
+                                     ConfigOperation::AddFeature("serde".to_string()),
+                                     ConfigOperation::AddFeature("log".to_string()),
+                                     ConfigOperation::SetBuildProfile("dev".into()),
+                                     ConfigOperation::AddInstallArg("--locked".to_string()),
+                                     ConfigOperation::AddInstallArg("--offline".to_string()),
+                                     ConfigOperation::SetEnforceLock(true),
+                                     ConfigOperation::SetEnvironment("RUSTC_WRAPPER".to_string(), "sccache".to_string()),
+                                     ConfigOperation::ClearEnvironment("CC".to_string())]));
+
+    assert_eq!(PackageConfig::default().inherit(&base), base);
+    assert_eq!(base.inherit(&PackageConfig::default()), base);
+}
+
+#[test]
+fn read_defaults() {
+    let td = temp_dir().join("cargo_update-test").join("package_config-read_defaults");
+    let _ = fs::create_dir_all(&td);
+    let config_file = td.join(".install_config.toml");
+
+    fs::write(&config_file,
+              &b"[\"*\"]\n\
+                 toolchain = \"nightly\"\n\
+                 default_features = true\n\
+                 features = [\"serde\"]\n\
+                 \n\
+                 [cargo-update]\n\
+                 default_features = true\n\
+                 features = [\"log\"]\n\
+                 \n\
+                 [racer]\n\
+                 toolchain = \"stable\"\n\
+                 default_features = false\n\
+                 features = []\n"[..])
+        .unwrap();
+
+    let raw = PackageConfig::read_unmerged(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(raw["cargo-update"].toolchain, None);
+
+    let read = PackageConfig::read(&config_file, Path::new("/ENOENT")).unwrap();
+    assert_eq!(read.keys().collect::<Vec<_>>(), ["*", "cargo-update", "racer"]);
+    assert_eq!(read["*"], raw["*"]);
+    assert_eq!(read["cargo-update"].toolchain.as_deref(), Some("nightly"));
+    assert_eq!(read["cargo-update"].features.iter().collect::<Vec<_>>(), ["log", "serde"]);
+    assert_eq!(read["racer"].toolchain.as_deref(), Some("stable"));
+    assert!(!read["racer"].default_features);
+    assert_eq!(read["racer"].features.iter().collect::<Vec<_>>(), ["serde"]);
+
+    PackageConfig::write(&raw, &config_file).unwrap();
+    assert_eq!(PackageConfig::read_unmerged(&config_file, Path::new("/ENOENT")).unwrap(), raw);
+}
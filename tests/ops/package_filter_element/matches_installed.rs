@@ -0,0 +1,30 @@
+use cargo_update::ops::{PackageFilterElement, PackageSource, installed_time};
+use std::time::{SystemTime, Duration};
+use std::env::temp_dir;
+use std::{cmp, fs};
+
+
+#[test]
+fn installed_before() {
+    let filter = PackageFilterElement::parse("installed-before=90d").unwrap();
+    let now = SystemTime::now();
+
+    assert!(filter.matches_source(PackageSource::Git, None, Some(now - Duration::from_secs(91 * 24 * 60 * 60))));
+    assert!(!filter.matches_source(PackageSource::Git, None, Some(now - Duration::from_secs(89 * 24 * 60 * 60))));
+    assert!(!filter.matches_source(PackageSource::Path, None, None));
+}
+
+#[test]
+fn newest_executable() {
+    let bin_dir = temp_dir().join("cargo_update-test").join("matches_installed-newest_executable");
+    let _ = fs::create_dir_all(&bin_dir);
+    fs::write(bin_dir.join("first"), "").unwrap();
+    fs::write(bin_dir.join("second"), "").unwrap();
+    let first = fs::metadata(bin_dir.join("first")).unwrap().modified().unwrap();
+    let second = fs::metadata(bin_dir.join("second")).unwrap().modified().unwrap();
+
+    assert_eq!(installed_time(&bin_dir, &["first".to_string()]), Some(first));
+    assert_eq!(installed_time(&bin_dir, &["first".to_string(), "second".to_string(), "missing".to_string()]),
+               Some(cmp::max(first, second)));
+    assert_eq!(installed_time(&bin_dir, &["missing".to_string()]), None);
+}
@@ -0,0 +1,25 @@
+use cargo_update::ops::{PackageFilterElement, PackageSource};
+
+
+#[test]
+fn git_and_registry() {
+    let filter = [PackageFilterElement::parse("source=git").unwrap(), PackageFilterElement::parse("source=registry").unwrap()];
+
+    assert!(!filter.iter().all(|f| f.matches_source(PackageSource::Git, None, None)));
+    assert!(!filter.iter().all(|f| f.matches_source(PackageSource::Registry, None, None)));
+}
+
+#[test]
+fn git_without_config() {
+    assert!(PackageFilterElement::parse("source=git").unwrap().matches_source(PackageSource::Git, None, None));
+    assert!(!PackageFilterElement::parse("source=registry").unwrap().matches_source(PackageSource::Git, None, None));
+}
+
+#[test]
+fn needs_update() {
+    let filter = [PackageFilterElement::parse("source=git").unwrap(), PackageFilterElement::parse("needs-update=yes").unwrap()];
+
+    assert!(!filter.iter().all(|f| f.matches_source(PackageSource::Git, None, None)));
+    assert!(filter.iter().all(|f| f.matches_polled(true)));
+    assert!(!filter.iter().all(|f| f.matches_polled(false)));
+}
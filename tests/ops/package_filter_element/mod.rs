@@ -1 +1,3 @@
 mod parse;
+mod matches_source;
+mod matches_installed;
@@ -12,3 +12,35 @@ fn unrecognised() {
     assert_eq!(PackageFilterElement::parse("henlo=benlo"),
                Err(r#"Unrecognised filter key "henlo""#.to_string()));
 }
+
+#[test]
+fn invalid_version() {
+    assert_eq!(PackageFilterElement::parse("version=henlo"),
+               Err(r#"Filter version requirement "henlo" invalid: unexpected character 'h' while parsing major version number"#.to_string()));
+}
+
+#[test]
+fn unrecognised_source() {
+    assert_eq!(PackageFilterElement::parse("source=henlo"),
+               Err(r#"Unrecognised filter source "henlo""#.to_string()));
+}
+
+#[test]
+fn invalid_duration() {
+    assert_eq!(PackageFilterElement::parse("installed-before="),
+               Err(r#"Filter duration "" invalid: empty"#.to_string()));
+    assert_eq!(PackageFilterElement::parse("installed-before=90"),
+               Err(r#"Filter duration "90" invalid: missing unit after 90"#.to_string()));
+    assert_eq!(PackageFilterElement::parse("installed-before=d"),
+               Err(r#"Filter duration "d" invalid: expected number at "d""#.to_string()));
+    assert_eq!(PackageFilterElement::parse("installed-before=3y"),
+               Err(r#"Filter duration "3y" invalid: unrecognised unit 'y', expected one of s, m, h, d, w"#.to_string()));
+    assert_eq!(PackageFilterElement::parse("installed-before=99999999999999999999w"),
+               Err(r#"Filter duration "99999999999999999999w" invalid: number too large to fit in target type"#.to_string()));
+}
+
+#[test]
+fn invalid_needs_update() {
+    assert_eq!(PackageFilterElement::parse("needs-update=maybe"),
+               Err(r#"Filter needs-update value "maybe" invalid: expected yes or no"#.to_string()));
+}
@@ -1,4 +1,6 @@
-use cargo_update::ops::PackageFilterElement;
+use cargo_update::ops::{PackageFilterElement, PackageSource};
+use semver::VersionReq as SemverReq;
+use std::time::Duration;
 
 
 #[test]
@@ -6,3 +8,43 @@ fn toolchain() {
     assert_eq!(PackageFilterElement::parse("toolchain=nightly"),
                Ok(PackageFilterElement::Toolchain("nightly".to_string())));
 }
+
+#[test]
+fn version() {
+    assert_eq!(PackageFilterElement::parse("version=<1.0"),
+               Ok(PackageFilterElement::Version(SemverReq::parse("<1.0").unwrap())));
+}
+
+#[test]
+fn registry() {
+    assert_eq!(PackageFilterElement::parse("registry=crates-io"),
+               Ok(PackageFilterElement::Registry("crates-io".to_string())));
+    assert_eq!(PackageFilterElement::parse("registry=sparse+https://index.crates.io/"),
+               Ok(PackageFilterElement::Registry("sparse+https://index.crates.io/".to_string())));
+}
+
+#[test]
+fn source() {
+    assert_eq!(PackageFilterElement::parse("source=git"), Ok(PackageFilterElement::Source(PackageSource::Git)));
+    assert_eq!(PackageFilterElement::parse("source=registry"),
+               Ok(PackageFilterElement::Source(PackageSource::Registry)));
+    assert_eq!(PackageFilterElement::parse("source=path"), Ok(PackageFilterElement::Source(PackageSource::Path)));
+}
+
+#[test]
+fn installed_before() {
+    assert_eq!(PackageFilterElement::parse("installed-before=180d"),
+               Ok(PackageFilterElement::InstalledBefore(Duration::from_secs(180 * 24 * 60 * 60))));
+    assert_eq!(PackageFilterElement::parse("installed-before=2w3d4h5m6s"),
+               Ok(PackageFilterElement::InstalledBefore(Duration::from_secs(((17 * 24 + 4) * 60 + 5) * 60 + 6))));
+}
+
+#[test]
+fn needs_update() {
+    for v in &["yes", "true", "1"] {
+        assert_eq!(PackageFilterElement::parse(&format!("needs-update={}", v)), Ok(PackageFilterElement::NeedsUpdate(true)));
+    }
+    for v in &["no", "false", "0"] {
+        assert_eq!(PackageFilterElement::parse(&format!("needs-update={}", v)), Ok(PackageFilterElement::NeedsUpdate(false)));
+    }
+}
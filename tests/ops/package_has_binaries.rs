@@ -0,0 +1,76 @@
+use cargo_update::ops::{registry_package_has_binaries, package_has_binaries};
+use semver::Version as Semver;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+fn package(name: &str, manifest: &str, files: &[&str]) -> PathBuf {
+    let package_dir = temp_dir().join("cargo_update-test").join(format!("package_has_binaries-{}", name));
+    let _ = fs::remove_dir_all(&package_dir);
+    fs::create_dir_all(package_dir.join("src")).unwrap();
+    fs::write(package_dir.join("Cargo.toml"),
+              format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n{}", name, manifest))
+        .unwrap();
+    fs::write(package_dir.join("src").join("lib.rs"), "").unwrap();
+    for f in files {
+        let f = package_dir.join(f);
+        fs::create_dir_all(f.parent().unwrap()).unwrap();
+        fs::write(f, "fn main() {}\n").unwrap();
+    }
+    package_dir
+}
+
+
+#[test]
+fn library() {
+    assert_eq!(package_has_binaries(&package("library", "", &[])), Some(false));
+}
+
+#[test]
+fn main_rs() {
+    assert_eq!(package_has_binaries(&package("main_rs", "", &["src/main.rs"])), Some(true));
+}
+
+#[test]
+fn bin_dir() {
+    assert_eq!(package_has_binaries(&package("bin_dir-file", "", &["src/bin/tool.rs"])), Some(true));
+    assert_eq!(package_has_binaries(&package("bin_dir-dir", "", &["src/bin/tool/main.rs"])), Some(true));
+    assert_eq!(package_has_binaries(&package("bin_dir-other", "", &["src/bin/README.md"])), Some(false));
+}
+
+#[test]
+fn bin_targets() {
+    assert_eq!(package_has_binaries(&package("bin_targets", "autobins = false\n\n[[bin]]\nname = \"tool\"\npath = \"tool.rs\"\n", &["tool.rs"])),
+               Some(true));
+}
+
+#[test]
+fn no_autobins() {
+    assert_eq!(package_has_binaries(&package("no_autobins", "autobins = false\n", &["src/main.rs", "src/bin/tool.rs"])), Some(false));
+}
+
+#[test]
+fn no_manifest() {
+    let package_dir = package("no_manifest", "", &["src/main.rs"]);
+    fs::remove_file(package_dir.join("Cargo.toml")).unwrap();
+    assert_eq!(package_has_binaries(&package_dir), None);
+}
+
+#[test]
+fn registry() {
+    let cargo_dir = temp_dir().join("cargo_update-test").join("package_has_binaries-registry");
+    let _ = fs::remove_dir_all(&cargo_dir);
+    fs::create_dir_all(cargo_dir.join("registry").join("index").join("index.crates.io-1949cf8c6b5b557f")).unwrap();
+
+    let source_dir = cargo_dir.join("registry").join("src").join("index.crates.io-1949cf8c6b5b557f");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::rename(package("clippy_lints", "", &[]), source_dir.join("clippy_lints-0.1.0")).unwrap();
+
+    assert_eq!(registry_package_has_binaries(&cargo_dir, "sparse+https://index.crates.io/", "clippy_lints", &Semver::parse("0.1.0").unwrap()),
+               Some(false));
+    assert_eq!(registry_package_has_binaries(&cargo_dir, "sparse+https://index.crates.io/", "clippy_lints", &Semver::parse("0.2.0").unwrap()),
+               None);
+    assert_eq!(registry_package_has_binaries(&cargo_dir, "https://github.com/rust-lang/crates.io-index", "clippy_lints", &Semver::parse("0.1.0").unwrap()),
+               None);
+}
@@ -0,0 +1,54 @@
+use cargo_update::ops::{package_name_glob_matches, expand_package_globs};
+use cargo_update::package_parse;
+use semver::VersionReq as SemverReq;
+
+
+#[test]
+fn exact() {
+    assert!(package_name_glob_matches("cargo-update", "cargo-update"));
+    assert!(!package_name_glob_matches("cargo-update", "cargo-updat"));
+    assert!(!package_name_glob_matches("cargo-updat", "cargo-update"));
+}
+
+#[test]
+fn star() {
+    assert!(package_name_glob_matches("cargo-update", "*"));
+    assert!(package_name_glob_matches("cargo-update", "cargo-*"));
+    assert!(package_name_glob_matches("cargo-", "cargo-*"));
+    assert!(package_name_glob_matches("cargo-update", "*update"));
+    assert!(package_name_glob_matches("cargo-update", "c*-*e"));
+    assert!(package_name_glob_matches("cargo-update", "**-**"));
+    assert!(!package_name_glob_matches("treesize", "cargo-*"));
+    assert!(!package_name_glob_matches("cargo-update", "*-outdated"));
+}
+
+#[test]
+fn question_mark() {
+    assert!(package_name_glob_matches("rustfmt", "rust???"));
+    assert!(package_name_glob_matches("cargo-update", "cargo?update"));
+    assert!(!package_name_glob_matches("rustfmt", "rust??"));
+    assert!(!package_name_glob_matches("rust", "rust?"));
+}
+
+#[test]
+fn expand_suffixes() {
+    let to_update = [package_parse("(file:///usr/local/share/cargo):cargo-*:=1.2.3").unwrap()];
+    assert_eq!(expand_package_globs(&to_update, &["cargo-update", "treesize", "cargo-outdated"]),
+               (vec![("cargo-update".to_string(), Some(SemverReq::parse("=1.2.3").unwrap()), "file:///usr/local/share/cargo".to_string(), true),
+                     ("cargo-outdated".to_string(), Some(SemverReq::parse("=1.2.3").unwrap()), "file:///usr/local/share/cargo".to_string(), true)],
+                vec![]));
+}
+
+#[test]
+fn expand_unmatched() {
+    let to_update = [package_parse("rust*").unwrap(), package_parse("racer").unwrap()];
+    assert_eq!(expand_package_globs(&to_update, &["cargo-update"]),
+               (vec![package_parse("racer").unwrap()], vec!["rust*".to_string()]));
+}
+
+#[test]
+fn expand_overlapping() {
+    let to_update = [package_parse("cargo-update:1.0.0").unwrap(), package_parse("cargo-*").unwrap()];
+    assert_eq!(expand_package_globs(&to_update, &["cargo-update", "cargo-outdated"]),
+               (vec![package_parse("cargo-update:1.0.0").unwrap(), package_parse("cargo-outdated").unwrap()], vec![]));
+}
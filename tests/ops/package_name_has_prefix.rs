@@ -0,0 +1,21 @@
+use cargo_update::ops::package_name_has_prefix;
+
+
+#[test]
+fn single() {
+    assert!(package_name_has_prefix("cargo-update", &["cargo-"]));
+    assert!(!package_name_has_prefix("treesize", &["cargo-"]));
+}
+
+#[test]
+fn multiple() {
+    let prefixes = ["cargo-".to_string(), "rust".to_string()];
+    assert!(package_name_has_prefix("cargo-update", &prefixes));
+    assert!(package_name_has_prefix("rustfmt", &prefixes));
+    assert!(!package_name_has_prefix("treesize", &prefixes));
+}
+
+#[test]
+fn none() {
+    assert!(!package_name_has_prefix("cargo-update", &[] as &[&str]));
+}
@@ -0,0 +1,120 @@
+use cargo_update::ops::{InstalledPackage, RegistryPackage, GitRepoPackage, PathPackage, parse_any};
+use semver::Version as Semver;
+use git2::{Error as GitError, Oid};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+
+#[test]
+fn registry() {
+    assert_eq!(parse_any("racer 1.2.10 (registry+https://github.com/rust-lang/crates.io-index)", vec!["racer".to_string()]),
+               Some(InstalledPackage::Registry(RegistryPackage {
+                   name: "racer".to_string(),
+                   registry: "https://github.com/rust-lang/crates.io-index".to_string(),
+                   version: Some(Semver::parse("1.2.10").unwrap()),
+                   newest_version: None,
+                   alternative_version: None,
+                   version_req: None,
+                   newest_satisfying_version: None,
+                   pinned_version: None,
+                   versions_behind: None,
+                   current_yanked: false,
+                   executables: vec!["racer".to_string()],
+               })));
+}
+
+#[test]
+fn sparse() {
+    assert_eq!(parse_any("racer 1.2.10 (sparse+https://index.crates.io/)", vec!["racer".to_string()]),
+               Some(InstalledPackage::Registry(RegistryPackage {
+                   name: "racer".to_string(),
+                   registry: "https://index.crates.io/".to_string(),
+                   version: Some(Semver::parse("1.2.10").unwrap()),
+                   newest_version: None,
+                   alternative_version: None,
+                   version_req: None,
+                   newest_satisfying_version: None,
+                   pinned_version: None,
+                   versions_behind: None,
+                   current_yanked: false,
+                   executables: vec!["racer".to_string()],
+               })));
+}
+
+#[test]
+fn git() {
+    assert_eq!(parse_any("alacritty 0.1.0 (git+https://github.com/jwilm/alacritty#eb231b3e70b87875df4bdd1974d5e94704024d70)",
+                         vec!["alacritty".to_string()]),
+               Some(InstalledPackage::Git(git_package("alacritty", "https://github.com/jwilm/alacritty", None, "eb231b3e70b87875df4bdd1974d5e94704024d70"))));
+}
+
+#[test]
+fn git_branch() {
+    assert_eq!(parse_any("chattium-oxide-client 0.1.0 \
+                          (git+https://github.com/nabijaczleweli/chattium-oxide-client?branch=master#108a7b94f0e0dcb2a875f70fc0459d5a682df14c)",
+                         vec!["chattium-oxide-client".to_string()]),
+               Some(InstalledPackage::Git(git_package("chattium-oxide-client",
+                                                      "https://github.com/nabijaczleweli/chattium-oxide-client",
+                                                      Some("master"),
+                                                      "108a7b94f0e0dcb2a875f70fc0459d5a682df14c"))));
+}
+
+#[test]
+fn git_tag() {
+    assert_eq!(parse_any("treesize 0.2.1 (git+https://github.com/melak47/treesize-rs?tag=v0.2.1#5f7885749c4d7e48869b1fc0be4d430601cdbbfa)",
+                         vec!["treesize".to_string()]),
+               Some(InstalledPackage::Git(git_package("treesize",
+                                                      "https://github.com/melak47/treesize-rs",
+                                                      None,
+                                                      "5f7885749c4d7e48869b1fc0be4d430601cdbbfa"))));
+}
+
+#[test]
+fn git_rev() {
+    assert_eq!(parse_any("treesize 0.2.1 (git+https://github.com/melak47/treesize-rs?rev=5f78857#5f7885749c4d7e48869b1fc0be4d430601cdbbfa)",
+                         vec!["treesize".to_string()]),
+               Some(InstalledPackage::Git(git_package("treesize",
+                                                      "https://github.com/melak47/treesize-rs",
+                                                      None,
+                                                      "5f7885749c4d7e48869b1fc0be4d430601cdbbfa"))));
+}
+
+#[test]
+fn git_registry_lookalike() {
+    assert_eq!(parse_any("racer 1.2.10 (git+https://github.com/racer-rust/racer?branch=registry+sparse#eb231b3e70b87875df4bdd1974d5e94704024d70)",
+                         vec!["racer".to_string()]),
+               Some(InstalledPackage::Git(git_package("racer",
+                                                      "https://github.com/racer-rust/racer",
+                                                      Some("registry sparse"),
+                                                      "eb231b3e70b87875df4bdd1974d5e94704024d70"))));
+}
+
+#[cfg(unix)]
+#[test]
+fn path() {
+    assert_eq!(parse_any("treesize 0.2.1 (path+file:///home/nabijaczleweli/treesize-rs)", vec!["treesize".to_string()]),
+               Some(InstalledPackage::Path(PathPackage {
+                   name: "treesize".to_string(),
+                   version: Semver::parse("0.2.1").unwrap(),
+                   path: PathBuf::from("/home/nabijaczleweli/treesize-rs"),
+                   executables: vec!["treesize".to_string()],
+               })));
+}
+
+#[test]
+fn unsupported() {
+    assert_eq!(parse_any("treesize 0.2.1 (local+file:///home/nabijaczleweli/treesize-rs)", vec![]), None);
+    assert_eq!(parse_any("treesize 0.2.1", vec![]), None);
+}
+
+
+fn git_package(name: &str, url: &str, branch: Option<&str>, id: &str) -> GitRepoPackage {
+    GitRepoPackage {
+        name: name.to_string(),
+        url: url.to_string(),
+        branch: branch.map(str::to_string),
+        id: Oid::from_str(id).unwrap(),
+        newest_id: Err(GitError::from_str("")),
+        executables: vec![name.to_string()],
+    }
+}
@@ -0,0 +1,104 @@
+use cargo_update::ops::{ConfigOperation, PackageConfig, RegistryPackage, UpdateKind, plan_updates};
+use semver::{VersionReq as SemverReq, Version as Semver};
+use std::collections::BTreeMap;
+
+
+#[test]
+fn kinds() {
+    let packages = [package("racer", Some("1.2.10"), Some("2.0.6")),
+                    package("checksums", Some("0.5.2"), Some("0.5.2")),
+                    package("treesize", None, Some("0.2.1")),
+                    package("rustfmt", Some("0.6.2"), Some("0.6.3")),
+                    package("cargo-count", Some("0.2.2"), None)];
+    let plan = plan_updates(&packages, &BTreeMap::new(), false, false);
+
+    assert_eq!(plan.packages.iter().map(|p| &p.name[..]).collect::<Vec<_>>(),
+               ["racer", "checksums", "treesize", "rustfmt", "cargo-count"]);
+    assert_eq!(plan.packages.iter().map(|p| p.kind).collect::<Vec<_>>(),
+               [UpdateKind::Update, UpdateKind::Reinstall, UpdateKind::Install, UpdateKind::Update, UpdateKind::Unavailable]);
+    assert_eq!(plan.packages.iter().map(|p| p.needs_update).collect::<Vec<_>>(), [true, false, true, true, false]);
+    assert_eq!(plan.packages.iter().map(|p| p.update).collect::<Vec<_>>(), [true, false, true, true, false]);
+    assert_eq!(plan.packages.iter().map(|p| p.breaking).collect::<Vec<_>>(), [true, false, false, false, false]);
+    assert_eq!(plan.packages[0].version, Some(Semver::parse("1.2.10").unwrap()));
+    assert_eq!(plan.packages[0].update_to_version, Some(Semver::parse("2.0.6").unwrap()));
+}
+
+#[test]
+fn force() {
+    let packages = [package("checksums", Some("0.5.2"), Some("0.5.2")), package("cargo-count", Some("0.2.2"), None)];
+    let plan = plan_updates(&packages, &BTreeMap::new(), false, true);
+
+    assert_eq!(plan.packages.iter().map(|p| p.needs_update).collect::<Vec<_>>(), [false, false]);
+    assert_eq!(plan.packages.iter().map(|p| p.update).collect::<Vec<_>>(), [true, false]);
+}
+
+#[test]
+fn rebuild() {
+    let packages = [package("checksums", Some("0.5.2"), Some("0.5.2")),
+                    package("racer", Some("2.0.6"), Some("1.2.10")),
+                    package("rustfmt", Some("0.6.2"), Some("0.6.3")),
+                    package("cargo-count", Some("0.2.2"), None)];
+    let mut plan = plan_updates(&packages, &BTreeMap::new(), false, false);
+    plan.rebuild_up_to_date();
+
+    assert_eq!(plan.packages.iter().map(|p| p.needs_update).collect::<Vec<_>>(), [false, false, true, false]);
+    assert_eq!(plan.packages.iter().map(|p| p.update).collect::<Vec<_>>(), [true, false, true, false]);
+    assert_eq!(plan.packages[0].kind, UpdateKind::Reinstall);
+}
+
+#[test]
+fn rebuild_target_version() {
+    let packages = [package("racer", Some("1.2.10"), Some("2.0.6"))];
+    let mut configuration = BTreeMap::new();
+    configuration.insert("racer".to_string(),
+                         PackageConfig::from(&[ConfigOperation::SetTargetVersion(SemverReq::parse("~1.2").unwrap())]));
+
+    let mut plan = plan_updates(&packages, &configuration, false, false);
+    plan.rebuild_up_to_date();
+    assert!(!plan.packages[0].needs_update);
+    assert!(plan.packages[0].update);
+    assert_eq!(plan.packages[0].target_version, Some(SemverReq::parse("~1.2").unwrap()));
+}
+
+#[test]
+fn downdate() {
+    let packages = [package("racer", Some("2.0.6"), Some("1.2.10"))];
+
+    let plan = plan_updates(&packages, &BTreeMap::new(), false, false);
+    assert_eq!(plan.packages[0].kind, UpdateKind::Downdate);
+    assert!(!plan.packages[0].update);
+
+    let plan = plan_updates(&packages, &BTreeMap::new(), true, false);
+    assert_eq!(plan.packages[0].kind, UpdateKind::Downdate);
+    assert!(plan.packages[0].update);
+}
+
+#[test]
+fn target_version() {
+    let packages = [package("racer", Some("1.2.10"), Some("2.0.6"))];
+    let mut configuration = BTreeMap::new();
+    configuration.insert("racer".to_string(),
+                         PackageConfig::from(&[ConfigOperation::SetTargetVersion(SemverReq::parse("~1.2").unwrap())]));
+
+    let plan = plan_updates(&packages, &configuration, false, false);
+    assert_eq!(plan.packages[0].target_version, Some(SemverReq::parse("~1.2").unwrap()));
+    assert!(!plan.packages[0].needs_update);
+    assert!(!plan.packages[0].update);
+}
+
+
+fn package(name: &str, version: Option<&str>, newest_version: Option<&str>) -> RegistryPackage {
+    RegistryPackage {
+        name: name.to_string(),
+        registry: "https://github.com/rust-lang/crates.io-index".to_string(),
+        version: version.map(|v| Semver::parse(v).unwrap()),
+        newest_version: newest_version.map(|v| Semver::parse(v).unwrap()),
+        alternative_version: None,
+        version_req: None,
+        newest_satisfying_version: None,
+        pinned_version: None,
+        versions_behind: None,
+        current_yanked: false,
+        executables: vec![],
+    }
+}
@@ -0,0 +1,32 @@
+use cargo_update::ops::program_exists;
+use std::ffi::OsStr;
+use std::env::{self, temp_dir};
+use std::fs;
+
+
+#[test]
+fn dotted_name() {
+    let td = temp_dir().join("cargo_update-test").join("program_exists-dotted_name");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    fs::write(td.join(format!("foo.bar{}", env::consts::EXE_SUFFIX)), "").unwrap();
+
+    assert!(program_exists(OsStr::new("foo.bar"), Some(td.as_os_str())));
+    assert!(!program_exists(OsStr::new("foo"), Some(td.as_os_str())));
+}
+
+#[test]
+fn path() {
+    let td = temp_dir().join("cargo_update-test").join("program_exists-path");
+    let _ = fs::remove_dir_all(&td);
+    fs::create_dir_all(&td).unwrap();
+    fs::write(td.join("foo.bar"), "").unwrap();
+
+    assert!(program_exists(td.join("foo.bar").as_os_str(), None));
+    assert!(!program_exists(td.join("foo").as_os_str(), None));
+}
+
+#[test]
+fn no_path() {
+    assert!(!program_exists(OsStr::new("sh"), None));
+}
@@ -0,0 +1,54 @@
+use cargo_update::ops::proxy_excluded;
+
+
+#[test]
+fn exact_host() {
+    assert!(proxy_excluded("crates.io", "crates.io"));
+    assert!(proxy_excluded("Crates.IO", "crates.io"));
+    assert!(proxy_excluded("crates.io.", "crates.io"));
+    assert!(proxy_excluded("localhost", "example.com,localhost"));
+    assert!(proxy_excluded("localhost", " example.com , localhost "));
+
+    assert!(!proxy_excluded("crates.io", "crates.com"));
+    assert!(!proxy_excluded("crates.io", ",,"));
+}
+
+#[test]
+fn domain_suffix() {
+    assert!(proxy_excluded("index.crates.io", "crates.io"));
+    assert!(proxy_excluded("index.crates.io", ".crates.io"));
+    assert!(proxy_excluded("index.crates.io", "*.crates.io"));
+    assert!(proxy_excluded("a.b.corp.example", "corp.example"));
+
+    assert!(!proxy_excluded("notcrates.io", "crates.io"));
+    assert!(!proxy_excluded("crates.io", "index.crates.io"));
+}
+
+#[test]
+fn wildcard() {
+    assert!(proxy_excluded("github.com", "*"));
+    assert!(proxy_excluded("10.0.0.1", "crates.io,*"));
+}
+
+#[test]
+fn ip() {
+    assert!(proxy_excluded("127.0.0.1", "127.0.0.1"));
+    assert!(proxy_excluded("[::1]", "::1"));
+    assert!(proxy_excluded("[::1]", "[::1]"));
+
+    assert!(!proxy_excluded("127.0.0.2", "127.0.0.1"));
+}
+
+#[test]
+fn cidr() {
+    assert!(proxy_excluded("10.1.2.3", "10.0.0.0/8"));
+    assert!(proxy_excluded("192.168.1.20", "192.168.1.0/24"));
+    assert!(proxy_excluded("192.168.1.20", "0.0.0.0/0"));
+    assert!(proxy_excluded("[fd00::1]", "fd00::/8"));
+
+    assert!(!proxy_excluded("11.1.2.3", "10.0.0.0/8"));
+    assert!(!proxy_excluded("192.168.2.20", "192.168.1.0/24"));
+    assert!(!proxy_excluded("10.1.2.3", "10.0.0.0/33"));
+    assert!(!proxy_excluded("10.1.2.3", "fd00::/8"));
+    assert!(!proxy_excluded("crates.io", "10.0.0.0/8"));
+}
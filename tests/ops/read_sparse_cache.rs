@@ -0,0 +1,59 @@
+use cargo_update::ops::{read_sparse_cache, parse_sparse_cache, registry_index_dirs};
+use semver::Version as Semver;
+use std::env::temp_dir;
+use std::path::PathBuf;
+use std::fs;
+
+
+static HEX: &[u8] = include_bytes!("../../test-data/sparse-cache/hex");
+static HEX_VERSIONS: &[(&str, bool)] = &[("0.1.0", false),
+                                         ("0.2.0", false),
+                                         ("0.3.0", false),
+                                         ("0.3.1", false),
+                                         ("0.3.2", false),
+                                         ("0.4.0", false),
+                                         ("0.4.1", true),
+                                         ("0.4.2", false),
+                                         ("0.4.3", false)];
+
+
+#[test]
+fn captured() {
+    assert_eq!(parse_sparse_cache(HEX), Some(HEX_VERSIONS.iter().map(|&(v, y)| (Semver::parse(v).unwrap(), None, y)).collect()));
+}
+
+#[test]
+fn truncated() {
+    assert_eq!(parse_sparse_cache(&HEX[..HEX.len() / 2]), None);
+    assert_eq!(parse_sparse_cache(&HEX[..3]), None);
+    assert_eq!(parse_sparse_cache(b""), None);
+}
+
+#[test]
+fn from_cargo_dir() {
+    let cargo_dir = prep_cargo_dir("from_cargo_dir");
+
+    assert_eq!(read_sparse_cache(&cargo_dir, "index.crates.io-1949cf8c6b5b557f", "hex"),
+               Some(HEX_VERSIONS.iter().map(|&(v, y)| (Semver::parse(v).unwrap(), None, y)).collect()));
+    assert_eq!(read_sparse_cache(&cargo_dir, "index.crates.io-1949cf8c6b5b557f", "cargo-update"), None);
+    assert_eq!(read_sparse_cache(&cargo_dir, "index.crates.io-6f17d22bba15001f", "hex"), None);
+}
+
+#[test]
+fn index_dirs() {
+    let cargo_dir = prep_cargo_dir("index_dirs");
+    fs::create_dir_all(cargo_dir.join("registry").join("index").join("github.com-1ecc6299db9ec823")).unwrap();
+
+    assert_eq!(registry_index_dirs(&cargo_dir, "https://index.crates.io/"), ["index.crates.io-1949cf8c6b5b557f"]);
+    assert_eq!(registry_index_dirs(&cargo_dir, "https://example.com/index/"), Vec::<String>::new());
+}
+
+
+fn prep_cargo_dir(subname: &str) -> PathBuf {
+    let cargo_dir = temp_dir().join("cargo_update-test").join(format!("read_sparse_cache-{}", subname));
+    let cache_dir = cargo_dir.join("registry").join("index").join("index.crates.io-1949cf8c6b5b557f").join(".cache").join("3").join("h");
+    let _ = fs::remove_dir_all(&cargo_dir);
+    fs::create_dir_all(&cache_dir).unwrap();
+    fs::write(cache_dir.join("hex"), HEX).unwrap();
+    cargo_dir
+}
@@ -1 +1,5 @@
 mod parse;
+mod pull_version;
+mod needs_update_to;
+mod update_to_version;
+mod update_status;
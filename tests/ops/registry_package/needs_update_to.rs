@@ -0,0 +1,56 @@
+use cargo_update::ops::RegistryPackage;
+use semver::{VersionReq as SemverReq, Version as Semver};
+use std::str::FromStr;
+
+
+#[test]
+fn would_update() {
+    let package = package("1.7.2");
+    assert!(package.needs_update_to(&Semver::parse("1.7.3").unwrap(), None, None, false));
+    assert!(package.needs_update_to(&Semver::parse("2.0.0").unwrap(), None, None, false));
+}
+
+#[test]
+fn would_not_update() {
+    let package = package("1.7.2");
+    assert!(!package.needs_update_to(&Semver::parse("1.7.2").unwrap(), None, None, false));
+    assert!(!package.needs_update_to(&Semver::parse("1.0.0").unwrap(), None, None, false));
+    assert!(!package.needs_update_to(&Semver::parse("1.8.0-rc.1").unwrap(), None, None, false));
+    assert!(!package.needs_update_to(&Semver::parse("2.0.0").unwrap(), Some(&SemverReq::from_str("^1.7").unwrap()), None, false));
+}
+
+#[test]
+fn would_downdate() {
+    assert!(package("1.7.2").needs_update_to(&Semver::parse("1.0.0").unwrap(), None, None, true));
+}
+
+#[test]
+fn prerelease() {
+    assert!(package("1.7.2").needs_update_to(&Semver::parse("1.8.0-rc.1").unwrap(), None, Some(true), false));
+    assert!(package("1.8.0-rc.1").needs_update_to(&Semver::parse("1.8.0-rc.2").unwrap(), None, None, false));
+}
+
+#[test]
+fn build_metadata() {
+    assert!(!package("1.2.3").needs_update_to(&Semver::parse("1.2.3+b").unwrap(), None, None, false));
+    assert!(!package("1.2.3").needs_update_to(&Semver::parse("1.2.3+b").unwrap(), None, None, true));
+    assert!(!package("1.2.3+b").needs_update_to(&Semver::parse("1.2.3").unwrap(), None, None, false));
+    assert!(!package("1.2.3+b").needs_update_to(&Semver::parse("1.2.3+c").unwrap(), None, None, true));
+    assert!(package("1.2.3+b").needs_update_to(&Semver::parse("1.2.4").unwrap(), None, None, false));
+    assert!(package("1.2.3").needs_update_to(&Semver::parse("1.2.4+b").unwrap(), None, None, false));
+    assert!(package("1.2.4+b").needs_update_to(&Semver::parse("1.2.3+c").unwrap(), None, None, true));
+}
+
+#[test]
+fn prerelease_build_metadata() {
+    assert!(!package("1.2.3-rc.1").needs_update_to(&Semver::parse("1.2.3-rc.1+b").unwrap(), None, Some(true), false));
+    assert!(!package("1.2.3-rc.1+b").needs_update_to(&Semver::parse("1.2.3-rc.1+c").unwrap(), None, Some(true), true));
+    assert!(package("1.2.3-rc.1+b").needs_update_to(&Semver::parse("1.2.3-rc.2+a").unwrap(), None, None, false));
+    assert!(package("1.2.3-rc.1+b").needs_update_to(&Semver::parse("1.2.3").unwrap(), None, None, false));
+    assert!(!package("1.2.3+b").needs_update_to(&Semver::parse("1.2.3-rc.2+c").unwrap(), None, Some(true), false));
+}
+
+
+fn package(version: &str) -> RegistryPackage {
+    RegistryPackage::parse(&format!("racer {} (registry+https://github.com/rust-lang/crates.io-index)", version), vec![]).unwrap()
+}
@@ -12,7 +12,11 @@ fn main_registry() {
                    version: Some(Semver::parse("0.2.2").unwrap()),
                    newest_version: None,
                    alternative_version: None,
-                   max_version: None,
+                   version_req: None,
+                   newest_satisfying_version: None,
+                   pinned_version: None,
+                   versions_behind: None,
+                   current_yanked: false,
                    executables: vec!["cc".to_string()],
                }));
 }
@@ -26,7 +30,29 @@ fn alt_registry() {
                    version: Some(Semver::parse("0.2.2").unwrap()),
                    newest_version: None,
                    alternative_version: None,
-                   max_version: None,
+                   version_req: None,
+                   newest_satisfying_version: None,
+                   pinned_version: None,
+                   versions_behind: None,
+                   current_yanked: false,
+                   executables: vec!["cc".to_string()],
+               }));
+}
+
+#[test]
+fn alt_registry_with_space() {
+    assert_eq!(RegistryPackage::parse("cargo-count 0.2.2 (registry+file:///C:/Program Files/cargo)", vec!["cc".to_string()]),
+               Some(RegistryPackage {
+                   name: "cargo-count".to_string(),
+                   registry: "file:///C:/Program Files/cargo".to_string(),
+                   version: Some(Semver::parse("0.2.2").unwrap()),
+                   newest_version: None,
+                   alternative_version: None,
+                   version_req: None,
+                   newest_satisfying_version: None,
+                   pinned_version: None,
+                   versions_behind: None,
+                   current_yanked: false,
                    executables: vec!["cc".to_string()],
                }));
 }
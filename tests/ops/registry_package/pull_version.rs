@@ -0,0 +1,119 @@
+use cargo_update::ops::{RegistryPackage, RegistryTree, Registry};
+use semver::{VersionReq as SemverReq, Version as Semver};
+use std::collections::BTreeMap;
+
+
+#[test]
+fn rust_version_compatible() {
+    let mut package = package("1.0.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, Some(&Semver::new(1, 80, 0)));
+    assert_eq!(package.newest_version, Some(Semver::parse("1.2.0").unwrap()));
+    assert_eq!(package.alternative_version, None);
+}
+
+#[test]
+fn rust_version_skipped() {
+    let mut package = package("1.0.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, Some(&Semver::new(1, 71, 1)));
+    assert_eq!(package.newest_version, Some(Semver::parse("1.1.0").unwrap()));
+    assert_eq!(package.alternative_version, Some(Semver::parse("1.2.0").unwrap()));
+    assert_eq!(package.versions_behind, Some(1));
+    assert!(package.needs_update(None, None, false));
+}
+
+#[test]
+fn rust_version_all_skipped() {
+    let mut package = package("1.0.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, Some(&Semver::new(1, 60, 0)));
+    assert_eq!(package.newest_version, Some(Semver::parse("1.0.0").unwrap()));
+    assert_eq!(package.alternative_version, Some(Semver::parse("1.2.0").unwrap()));
+    assert!(!package.needs_update(None, None, false));
+}
+
+#[test]
+fn rust_version_ignored() {
+    let mut package = package("1.0.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert_eq!(package.newest_version, Some(Semver::parse("1.2.0").unwrap()));
+    assert_eq!(package.alternative_version, None);
+}
+
+#[test]
+fn version_req() {
+    let mut package = RegistryPackage { version_req: Some(SemverReq::parse(">=1, <1.2").unwrap()), ..package("1.0.0") };
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert_eq!(package.newest_version, Some(Semver::parse("1.2.0").unwrap()));
+    assert_eq!(package.newest_satisfying_version, Some(Semver::parse("1.1.0").unwrap()));
+    assert_eq!(package.update_to_version(), Some(&Semver::parse("1.1.0").unwrap()));
+    assert!(package.needs_update(None, None, false));
+}
+
+#[test]
+fn version_req_rust_version_skipped() {
+    let mut package = RegistryPackage { version_req: Some(SemverReq::parse("^1").unwrap()), ..package("1.0.0") };
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, Some(&Semver::new(1, 71, 1)));
+    assert_eq!(package.newest_satisfying_version, Some(Semver::parse("1.1.0").unwrap()));
+}
+
+#[test]
+fn version_req_unsatisfied() {
+    let mut package = RegistryPackage { version_req: Some(SemverReq::parse("^2").unwrap()), ..package("1.0.0") };
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert_eq!(package.newest_satisfying_version, None);
+    assert_eq!(package.update_to_version(), None);
+}
+
+#[test]
+fn current_yanked() {
+    let mut newer = package("1.3.0");
+    newer.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert!(newer.current_yanked);
+    assert!(newer.needs_update(None, None, false));
+    assert_eq!(newer.update_to_version(), Some(&Semver::parse("1.2.0").unwrap()));
+
+    let mut older = package("1.1.1");
+    older.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert!(older.current_yanked);
+    assert!(older.needs_update(None, None, false));
+}
+
+#[test]
+fn current_not_yanked() {
+    let mut package = package("1.1.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, Some(&Semver::new(1, 60, 0)));
+    assert!(!package.current_yanked);
+    assert!(!package.needs_update(None, None, false));
+}
+
+#[test]
+fn current_newer_not_in_index() {
+    let mut package = package("1.4.0");
+    package.pull_version(&RegistryTree::Sparse(()), &registry(), None, None);
+    assert!(!package.current_yanked);
+    assert!(!package.needs_update(None, None, false));
+    assert!(package.needs_update(None, None, true));
+}
+
+#[test]
+fn has_version_data() {
+    assert!(package("1.0.0").has_version_data(&RegistryTree::Sparse(()), &registry()));
+    assert!(!RegistryPackage::parse("racer 1.2.10 (sparse+https://index.crates.io/)", vec![])
+        .unwrap()
+        .has_version_data(&RegistryTree::Sparse(()), &registry()));
+}
+
+
+fn package(version: &str) -> RegistryPackage {
+    RegistryPackage::parse(&format!("synthetic {} (sparse+https://index.crates.io/)", version), vec![]).unwrap()
+}
+
+fn registry() -> Registry {
+    let mut registry = BTreeMap::new();
+    registry.insert("synthetic".to_string(),
+                    vec![(Semver::parse("1.0.0").unwrap(), None, false),
+                         (Semver::parse("1.1.0").unwrap(), Some(Semver::new(1, 70, 0)), false),
+                         (Semver::parse("1.1.1").unwrap(), Some(Semver::new(1, 70, 0)), true),
+                         (Semver::parse("1.2.0").unwrap(), Some(Semver::new(1, 75, 0)), false),
+                         (Semver::parse("1.3.0").unwrap(), Some(Semver::new(1, 75, 0)), true)]);
+    Registry::Sparse(registry)
+}
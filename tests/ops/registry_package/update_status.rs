@@ -0,0 +1,87 @@
+use cargo_update::ops::{RegistryPackage, UpdateStatus};
+use semver::{VersionReq as SemverReq, Version as Semver};
+use std::str::FromStr;
+
+
+#[test]
+fn update() {
+    assert_eq!(package("1.7.2", Some("2.0.6")).update_status(None, None, false),
+               UpdateStatus::Update { to: Semver::parse("2.0.6").unwrap() });
+    assert_eq!(package("2.0.7", Some("2.0.6")).update_status(None, None, true),
+               UpdateStatus::Update { to: Semver::parse("2.0.6").unwrap() });
+}
+
+#[test]
+fn up_to_date() {
+    assert_eq!(package("1.7.2", Some("1.7.2")).update_status(None, None, false), UpdateStatus::UpToDate);
+    assert_eq!(package("1.7.2", Some("1.7.2")).update_status(None, None, true), UpdateStatus::UpToDate);
+}
+
+#[test]
+fn outside_requirement() {
+    let req = SemverReq::from_str("^1.7").unwrap();
+    assert_eq!(package("1.7.2", Some("2.0.6")).update_status(Some(&req), None, false), UpdateStatus::OutsideRequirement(req.clone()));
+    assert_eq!(package("1.7.2", Some("1.8.0")).update_status(Some(&req), None, false),
+               UpdateStatus::Update { to: Semver::parse("1.8.0").unwrap() });
+}
+
+#[test]
+fn prerelease_skipped() {
+    assert_eq!(package("1.7.2", Some("2.0.0-rc.1")).update_status(None, None, false),
+               UpdateStatus::PrereleaseSkipped { latest: Semver::parse("2.0.0-rc.1").unwrap() });
+    assert_eq!(package("1.7.2", Some("2.0.0-rc.1")).update_status(None, Some(true), false),
+               UpdateStatus::Update { to: Semver::parse("2.0.0-rc.1").unwrap() });
+}
+
+#[test]
+fn yanked() {
+    let pkg = RegistryPackage { current_yanked: true, ..package("1.7.3", Some("1.7.2")) };
+    assert_eq!(pkg.update_status(None, None, false), UpdateStatus::Yanked { to: Semver::parse("1.7.2").unwrap() });
+    assert!(pkg.needs_update(None, None, false));
+
+    let pkg = RegistryPackage { current_yanked: true, ..package("1.7.1", Some("1.7.2")) };
+    assert_eq!(pkg.update_status(None, None, false), UpdateStatus::Yanked { to: Semver::parse("1.7.2").unwrap() });
+}
+
+#[test]
+fn installed_newer() {
+    let pkg = package("1.8.0-patched", Some("1.7.2"));
+    assert!(pkg.installed_newer());
+    assert_eq!(pkg.update_status(None, None, false), UpdateStatus::InstalledNewer { latest: Semver::parse("1.7.2").unwrap() });
+    assert!(!pkg.needs_update(None, None, false));
+    assert!(pkg.needs_update(None, None, true));
+    assert_eq!(pkg.update_status(None, None, true), UpdateStatus::Update { to: Semver::parse("1.7.2").unwrap() });
+
+    assert!(!package("1.7.2", Some("1.7.2")).installed_newer());
+    assert!(!package("1.7.2", Some("1.8.0")).installed_newer());
+    assert!(!package("1.7.2", None).installed_newer());
+}
+
+#[test]
+fn no_versions() {
+    assert_eq!(package("1.7.2", None).update_status(None, None, false), UpdateStatus::NoVersions);
+}
+
+#[test]
+fn matches_needs_update() {
+    for &(installed, newest, downdate) in &[("1.7.2", Some("2.0.6"), false),
+                                             ("1.7.2", Some("1.7.2"), false),
+                                             ("1.7.3", Some("1.7.2"), false),
+                                             ("1.7.3", Some("1.7.2"), true),
+                                             ("1.7.2", Some("2.0.0-rc.1"), false),
+                                             ("1.7.2", None, false)] {
+        let pkg = package(installed, newest);
+        assert_eq!(pkg.needs_update(None, None, downdate),
+                   matches!(pkg.update_status(None, None, downdate), UpdateStatus::Update { .. }));
+    }
+
+    let pkg = RegistryPackage { current_yanked: true, ..package("1.7.3", Some("1.7.2")) };
+    assert_eq!(pkg.needs_update(None, None, false), matches!(pkg.update_status(None, None, false), UpdateStatus::Yanked { .. }));
+}
+
+
+fn package(version: &str, newest: Option<&str>) -> RegistryPackage {
+    let mut pkg = RegistryPackage::parse(&format!("racer {} (registry+https://github.com/rust-lang/crates.io-index)", version), vec![]).unwrap();
+    pkg.newest_version = newest.map(|n| Semver::parse(n).unwrap());
+    pkg
+}
@@ -0,0 +1,52 @@
+use cargo_update::ops::{RegistryPackage, parse_version_spec, intersect_packages};
+use semver::{VersionReq as SemverReq, Version as Semver};
+
+
+#[test]
+fn version_req() {
+    let mut package = package("1.7.2", Some("2.0.6"), Some("2.0.5"), false);
+    assert_eq!(package.version_req, Some(SemverReq::parse("<=2.0.5").unwrap()));
+    assert_eq!(package.pinned_version, None);
+    assert_eq!(package.update_to_version(), None);
+
+    package.newest_satisfying_version = Some(Semver::parse("2.0.4").unwrap());
+    assert_eq!(package.update_to_version(), Some(&Semver::parse("2.0.4").unwrap()));
+}
+
+#[test]
+fn pinned() {
+    let package = package("1.7.2", Some("2.0.6"), Some("1.0.0"), true);
+    assert_eq!(package.version_req, None);
+    assert_eq!(package.pinned_version, Some(Semver::parse("1.0.0").unwrap()));
+    assert_eq!(package.update_to_version(), Some(&Semver::parse("1.0.0").unwrap()));
+    assert!(!package.needs_update(None, None, false));
+    assert!(package.needs_update(None, None, true));
+}
+
+#[test]
+fn pinned_prerelease() {
+    let package = package("1.7.2", Some("2.0.6"), Some("2.0.0-rc.1"), true);
+    assert_eq!(package.update_to_version(), Some(&Semver::parse("2.0.0-rc.1").unwrap()));
+    assert!(package.needs_update(None, None, false));
+}
+
+#[test]
+fn pinned_yanked() {
+    assert_eq!(package("1.7.2", None, Some("1.0.0"), true).update_to_version(), None);
+}
+
+
+fn package(version: &str, newest: Option<&str>, spec_version: Option<&str>, exact: bool) -> RegistryPackage {
+    let mut package =
+        intersect_packages(&[RegistryPackage::parse(&format!("racer {} (registry+https://github.com/rust-lang/crates.io-index)", version), vec![])
+                               .unwrap()],
+                           &[("racer".to_string(),
+                              spec_version.map(|v| parse_version_spec(&format!("{}{}", if exact { "=" } else { "" }, v)).unwrap().0),
+                              "https://github.com/rust-lang/crates.io-index".to_string(),
+                              exact)],
+                           false,
+                           &[])
+            .remove(0);
+    package.newest_version = newest.map(|v| Semver::parse(v).unwrap());
+    package
+}
@@ -0,0 +1,34 @@
+use cargo_update::ops::shell_command;
+use std::env::temp_dir;
+use std::ffi::OsStr;
+use std::fs;
+
+
+#[test]
+fn success() {
+    let td = temp_dir().join("cargo_update-test").join("shell_command-success");
+
+    assert!(shell_command(OsStr::new("exit 0"), &td).status().unwrap().success());
+}
+
+#[test]
+fn failure_aborts() {
+    let td = temp_dir().join("cargo_update-test").join("shell_command-failure_aborts");
+
+    let status = shell_command(OsStr::new("exit 7"), &td).status().unwrap();
+    assert!(!status.success());
+    assert_eq!(status.code(), Some(7));
+}
+
+#[test]
+fn runs_in_dir() {
+    let td = temp_dir().join("cargo_update-test").join("shell_command-runs_in_dir");
+    let _ = fs::remove_dir_all(&td);
+
+    assert!(shell_command(OsStr::new("echo %CARGO_UPDATE_PACKAGES% $CARGO_UPDATE_PACKAGES > packages"), &td)
+        .env("CARGO_UPDATE_PACKAGES", "3")
+        .status()
+        .unwrap()
+        .success());
+    assert!(fs::read_to_string(td.join("packages")).unwrap().contains('3'));
+}
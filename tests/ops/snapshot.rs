@@ -0,0 +1,72 @@
+use cargo_update::ops::{ConfigOperation, GitRepoPackage, PackageConfig, RegistryPackage, SnapshotPackage};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env::temp_dir;
+use std::fs;
+
+
+fn packages() -> (Vec<RegistryPackage>, Vec<GitRepoPackage>) {
+    (vec![RegistryPackage::parse("checksums 0.5.2 (registry+https://github.com/rust-lang/crates.io-index)", vec!["checksums".to_string()]).unwrap(),
+          RegistryPackage::parse("racer 1.2.10 (registry+file:///usr/local/share/cargo)", vec!["racer".to_string()]).unwrap()],
+     vec![GitRepoPackage::parse("alacritty 0.1.0 (git+https://github.com/jwilm/alacritty#eb231b3e70b87875df4bdd1974d5e94704024d70)",
+                                vec!["alacritty".to_string()])
+              .unwrap()])
+}
+
+
+#[test]
+fn record() {
+    let (registry, git) = packages();
+    let mut configuration = BTreeMap::new();
+    configuration.insert("alacritty".to_string(),
+                         PackageConfig::from(&[ConfigOperation::SetToolchain("nightly".to_string()), ConfigOperation::AddFeature("wayland".to_string())]));
+
+    let snapshot = SnapshotPackage::record(&registry, &git, &configuration);
+    assert_eq!(snapshot.keys().collect::<Vec<_>>(), ["alacritty", "checksums", "racer"]);
+    assert_eq!(snapshot["racer"],
+               SnapshotPackage {
+                   version: Some("1.2.10".to_string()),
+                   registry: Some("file:///usr/local/share/cargo".to_string()),
+                   git: None,
+                   rev: None,
+                   features: BTreeSet::new(),
+                   toolchain: None,
+               });
+    assert_eq!(snapshot["alacritty"],
+               SnapshotPackage {
+                   version: None,
+                   registry: None,
+                   git: Some("https://github.com/jwilm/alacritty".to_string()),
+                   rev: Some("eb231b3e70b87875df4bdd1974d5e94704024d70".to_string()),
+                   features: vec!["wayland".to_string()].into_iter().collect(),
+                   toolchain: Some("nightly".to_string()),
+               });
+}
+
+#[test]
+fn roundtrip() {
+    let td = temp_dir().join("cargo_update-test").join("snapshot-roundtrip");
+    let _ = fs::create_dir_all(&td);
+    let (registry, git) = packages();
+    let snapshot = SnapshotPackage::record(&registry, &git, &BTreeMap::new());
+
+    SnapshotPackage::write(&snapshot, &td.join("tools.toml")).unwrap();
+    assert_eq!(SnapshotPackage::read(&td.join("tools.toml")), Ok(snapshot));
+}
+
+#[test]
+fn parse_git() {
+    let snapshot = SnapshotPackage::parse("[alacritty]\n\
+                                           git = \"https://github.com/jwilm/alacritty\"\n\
+                                           rev = \"eb231b3e70b87875df4bdd1974d5e94704024d70\"\n")
+        .unwrap();
+    assert_eq!(snapshot["alacritty"].rev, Some("eb231b3e70b87875df4bdd1974d5e94704024d70".to_string()));
+    assert_eq!(snapshot["alacritty"].version, None);
+}
+
+#[test]
+fn parse_invalid() {
+    assert!(SnapshotPackage::parse("[checksums]\nregistry = \"https://github.com/rust-lang/crates.io-index\"\n").is_err());
+    assert!(SnapshotPackage::parse("[checksums]\nversion = \"0.5.2\"\nrev = \"eb231b3e\"\n").is_err());
+    assert!(SnapshotPackage::parse("[checksums]\nversion = 5\n").is_err());
+    assert!(SnapshotPackage::parse("checksums = \"0.5.2\"\n").is_err());
+}
@@ -0,0 +1,67 @@
+use cargo_update::ops::{parse_rustup_toolchains, toolchain_installed};
+
+
+static TOOLCHAINS: &str = "stable-x86_64-unknown-linux-gnu (default)\n\
+                           beta-x86_64-unknown-linux-gnu\n\
+                           nightly-2024-01-01-x86_64-unknown-linux-gnu (override)\n\
+                           1.71.1-x86_64-unknown-linux-gnu\n\
+                           stage1\n";
+
+
+#[test]
+fn parse() {
+    assert_eq!(parse_rustup_toolchains(TOOLCHAINS),
+               vec!["stable-x86_64-unknown-linux-gnu".to_string(),
+                    "beta-x86_64-unknown-linux-gnu".to_string(),
+                    "nightly-2024-01-01-x86_64-unknown-linux-gnu".to_string(),
+                    "1.71.1-x86_64-unknown-linux-gnu".to_string(),
+                    "stage1".to_string()]);
+}
+
+#[test]
+fn channel() {
+    let installed = parse_rustup_toolchains(TOOLCHAINS);
+    assert!(toolchain_installed("stable", &installed));
+    assert!(toolchain_installed("beta", &installed));
+    assert!(!toolchain_installed("nightly", &installed));
+}
+
+#[test]
+fn dated() {
+    let installed = parse_rustup_toolchains(TOOLCHAINS);
+    assert!(toolchain_installed("nightly-2024-01-01", &installed));
+    assert!(!toolchain_installed("nightly-2024-01-02", &installed));
+}
+
+#[test]
+fn version() {
+    let installed = parse_rustup_toolchains(TOOLCHAINS);
+    assert!(toolchain_installed("1.71.1", &installed));
+    assert!(!toolchain_installed("1.71", &installed));
+    assert!(!toolchain_installed("1.70.0", &installed));
+}
+
+#[test]
+fn full_name() {
+    let installed = parse_rustup_toolchains(TOOLCHAINS);
+    assert!(toolchain_installed("stable-x86_64-unknown-linux-gnu", &installed));
+    assert!(!toolchain_installed("stable-x86_64-unknown-linux-musl", &installed));
+}
+
+#[test]
+fn custom() {
+    let installed = parse_rustup_toolchains(TOOLCHAINS);
+    assert!(toolchain_installed("stage1", &installed));
+    assert!(!toolchain_installed("stage2", &installed));
+}
+
+#[test]
+fn path() {
+    assert!(toolchain_installed("/opt/rust/toolchain", &[]));
+}
+
+#[test]
+fn none() {
+    assert_eq!(parse_rustup_toolchains("no installed toolchains\n"), Vec::<String>::new());
+    assert!(!toolchain_installed("stable", &[]));
+}
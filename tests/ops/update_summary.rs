@@ -0,0 +1,46 @@
+use cargo_update::ops::UpdateSummary;
+use std::env::temp_dir;
+use std::fs;
+
+
+#[test]
+fn empty() {
+    assert_eq!(UpdateSummary::default().to_json(), r#"{"updated":[],"failed":[],"up_to_date":[]}"#);
+}
+
+#[test]
+fn escaped() {
+    let summary = UpdateSummary {
+        updated: vec![],
+        failed: vec![("\"quoted\"".to_string(), -1)],
+        up_to_date: vec!["back\\slash".to_string()],
+    };
+    assert_eq!(summary.to_json(), r#"{"updated":[],"failed":[{"name":"\"quoted\"","code":-1}],"up_to_date":["back\\slash"]}"#);
+}
+
+#[test]
+fn exit_code() {
+    assert_eq!(UpdateSummary::default().exit_code(), None);
+
+    let summary = UpdateSummary {
+        updated: vec!["cargo-update".to_string()],
+        failed: vec![("treesize".to_string(), 2), ("racer".to_string(), 101)],
+        up_to_date: vec![],
+    };
+    assert_eq!(summary.exit_code(), Some(2));
+}
+
+#[test]
+fn write() {
+    let td = temp_dir().join("cargo_update-test").join("update_summary-write");
+    let _ = fs::create_dir_all(&td);
+
+    let summary = UpdateSummary {
+        updated: vec!["cargo-update".to_string(), "checksums".to_string()],
+        failed: vec![("treesize".to_string(), 101)],
+        up_to_date: vec!["racer".to_string()],
+    };
+    summary.write(&td.join("summary.json")).unwrap();
+    assert_eq!(fs::read_to_string(td.join("summary.json")).unwrap(),
+               r#"{"updated":["cargo-update","checksums"],"failed":[{"name":"treesize","code":101}],"up_to_date":["racer"]}"#);
+}
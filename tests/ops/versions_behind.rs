@@ -0,0 +1,31 @@
+use cargo_update::ops::{self, versions_behind};
+use semver::Version as Semver;
+use std::fs;
+
+
+#[test]
+fn several_behind() {
+    let versions = ops::crate_versions(&fs::read("test-data/checksums-versions.json").unwrap()).unwrap();
+    assert_eq!(versions_behind(&Semver::parse("0.3.0").unwrap(), &Semver::parse("0.5.2").unwrap(), versions.iter().map(|(v, _, _)| v)), 5);
+}
+
+#[test]
+fn up_to_date() {
+    let versions = ops::crate_versions(&fs::read("test-data/checksums-versions.json").unwrap()).unwrap();
+    assert_eq!(versions_behind(&Semver::parse("0.5.2").unwrap(), &Semver::parse("0.5.2").unwrap(), versions.iter().map(|(v, _, _)| v)), 0);
+}
+
+#[test]
+fn prerelease() {
+    let versions: Vec<_> = ["1.0.0", "1.1.0-alpha.1", "1.1.0-alpha.2", "1.1.0"].iter().map(|v| Semver::parse(v).unwrap()).collect();
+    assert_eq!(versions_behind(&versions[0], &versions[3], &versions), 1);
+    assert_eq!(versions_behind(&versions[0], &versions[2], &versions), 2);
+}
+
+#[test]
+fn build_metadata() {
+    let versions: Vec<_> = ["1.2.3", "1.2.3+b", "1.2.4+a", "1.2.4+b"].iter().map(|v| Semver::parse(v).unwrap()).collect();
+    assert_eq!(versions_behind(&versions[0], &versions[1], &versions), 0);
+    assert_eq!(versions_behind(&versions[1], &versions[0], &versions), 0);
+    assert_eq!(versions_behind(&versions[1], &versions[3], &versions), 2);
+}
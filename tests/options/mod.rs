@@ -0,0 +1,2 @@
+mod read_package_list;
+mod package_parse;
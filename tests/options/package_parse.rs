@@ -0,0 +1,59 @@
+use cargo_update::{package_parse_in, package_parse};
+use semver::VersionReq as SemverReq;
+
+
+#[test]
+fn max_version() {
+    assert_eq!(package_parse("cargo-update:1.2.3"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("<=1.2.3").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+}
+
+#[test]
+fn caret_requirement() {
+    assert_eq!(package_parse("cargo-update:^1.2"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("^1.2").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+}
+
+#[test]
+fn range_requirement() {
+    assert_eq!(package_parse("cargo-update:>=1,<2"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse(">=1, <2").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+}
+
+#[test]
+fn exact_version() {
+    assert_eq!(package_parse("cargo-update:=1.2.3"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("=1.2.3").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), true)));
+    assert_eq!(package_parse("(file:///usr/local/share/cargo):racer:=1.0.0"),
+               Ok(("racer".to_string(), Some(SemverReq::parse("=1.0.0").unwrap()), "file:///usr/local/share/cargo".to_string(), true)));
+}
+
+#[test]
+fn at_requirement() {
+    assert_eq!(package_parse("cargo-update@^1.2"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("^1.2").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+    assert_eq!(package_parse("cargo-update@1.2.3"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("<=1.2.3").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+    assert_eq!(package_parse("(file:///usr/local/share/cargo):racer@=1.0.0"),
+               Ok(("racer".to_string(), Some(SemverReq::parse("=1.0.0").unwrap()), "file:///usr/local/share/cargo".to_string(), true)));
+}
+
+#[test]
+fn no_version() {
+    assert_eq!(package_parse("cargo-update"), Ok(("cargo-update".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+}
+
+#[test]
+fn invalid() {
+    assert!(package_parse("cargo-update:=1.2").unwrap_err().starts_with("Version 1.2 provided for package cargo-update invalid: "));
+    assert!(package_parse("cargo-update:==1.2.3").is_err());
+    assert!(package_parse("cargo-update:^1.2.3.4").unwrap_err().starts_with("Version ^1.2.3.4 provided for package cargo-update invalid: "));
+}
+
+#[test]
+fn default_registry() {
+    assert_eq!(package_parse_in("cargo-update:1.2.3", "my-registry"),
+               Ok(("cargo-update".to_string(), Some(SemverReq::parse("<=1.2.3").unwrap()), "my-registry".to_string(), false)));
+    assert_eq!(package_parse_in("(https://github.com/rust-lang/crates.io-index):racer", "my-registry"),
+               Ok(("racer".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false)));
+}
@@ -0,0 +1,54 @@
+use cargo_update::{read_package_list_in, read_package_list};
+use semver::VersionReq as SemverReq;
+
+
+#[test]
+fn piped() {
+    assert_eq!(read_package_list(&b"cargo-update\nracer:1.2.10\n(file:///usr/local/share/cargo):checksums\n"[..]),
+               Ok(vec![("cargo-update".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                       ("racer".to_string(), Some(SemverReq::parse("<=1.2.10").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                       ("checksums".to_string(), None, "file:///usr/local/share/cargo".to_string(), false)]));
+}
+
+#[test]
+fn version_reqs() {
+    assert_eq!(read_package_list(&b"racer:^1.2\ncargo-update@>=1,<2\nchecksums@=0.5.2\n(file:///usr/local/share/cargo):treesize@0.3\n"[..]),
+               Ok(vec![("racer".to_string(), Some(SemverReq::parse("^1.2").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), false),
+                       ("cargo-update".to_string(),
+                        Some(SemverReq::parse(">=1,<2").unwrap()),
+                        "https://github.com/rust-lang/crates.io-index".to_string(),
+                        false),
+                       ("checksums".to_string(), Some(SemverReq::parse("=0.5.2").unwrap()), "https://github.com/rust-lang/crates.io-index".to_string(), true),
+                       ("treesize".to_string(), Some(SemverReq::parse("<=0.3").unwrap()), "file:///usr/local/share/cargo".to_string(), false)]));
+}
+
+#[test]
+fn default_registry() {
+    assert_eq!(read_package_list_in(&b"cargo-update\nracer@^1.2\n(file:///usr/local/share/cargo):checksums\n"[..], "my-registry"),
+               Ok(vec![("cargo-update".to_string(), None, "my-registry".to_string(), false),
+                       ("racer".to_string(), Some(SemverReq::parse("^1.2").unwrap()), "my-registry".to_string(), false),
+                       ("checksums".to_string(), None, "file:///usr/local/share/cargo".to_string(), false)]));
+}
+
+#[test]
+fn blank_lines() {
+    assert_eq!(read_package_list(&b"\n  cargo-update  \r\n\n"[..]),
+               Ok(vec![("cargo-update".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false)]));
+}
+
+#[test]
+fn comments() {
+    assert_eq!(read_package_list(&b"# Everyone's tools\ncargo-update\n  # racer:1.2.10\n"[..]),
+               Ok(vec![("cargo-update".to_string(), None, "https://github.com/rust-lang/crates.io-index".to_string(), false)]));
+}
+
+#[test]
+fn empty() {
+    assert_eq!(read_package_list(&b""[..]), Ok(vec![]));
+}
+
+#[test]
+fn invalid() {
+    assert!(read_package_list(&b"cargo-update\nracer:>>1\n"[..]).unwrap_err().starts_with("line 2: "));
+    assert!(read_package_list(&b"cargo-update@1.2.3.4\n"[..]).unwrap_err().starts_with("line 1: "));
+}